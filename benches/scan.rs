@@ -0,0 +1,104 @@
+//! Times the commands that scan whole XDG directories over generated configuration trees.
+//!
+//! Run with `cargo bench --bench scan`. Set `COSMIC_CTL_BENCH_ENTRIES` to change the tree sizes
+//! (comma-separated, default '1000,10000') and `COSMIC_CTL_BENCH_RUNS` the runs per command.
+
+use std::{
+    env, fs,
+    path::Path,
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
+use tempfile::TempDir;
+
+const ENTRIES_PER_VERSION: usize = 50;
+const VERSIONS_PER_COMPONENT: usize = 2;
+
+/// Fills `config_home` with `entry_count` entries spread over components and versions.
+/// Every tenth value spans several lines, like the larger COSMIC entries do.
+fn generate_tree(config_home: &Path, entry_count: usize) {
+    for index in 0..entry_count {
+        let component = index / (ENTRIES_PER_VERSION * VERSIONS_PER_COMPONENT);
+        let version = index / ENTRIES_PER_VERSION % VERSIONS_PER_COMPONENT + 1;
+        let dir = config_home
+            .join("cosmic")
+            .join(format!("com.example.Bench{}", component))
+            .join(format!("v{}", version));
+        fs::create_dir_all(&dir).unwrap();
+
+        let value = if index % 10 == 0 {
+            format!(
+                "(\n    index: {},\n    name: \"entry_{}\",\n    enabled: true,\n)",
+                index, index
+            )
+        } else {
+            index.to_string()
+        };
+        fs::write(
+            dir.join(format!("entry_{}", index % ENTRIES_PER_VERSION)),
+            value,
+        )
+        .unwrap();
+    }
+}
+
+fn run(config_home: &Path, args: &[&str]) -> Duration {
+    let started = Instant::now();
+    let status = Command::new(env!("CARGO_BIN_EXE_cosmic-ctl"))
+        .env("XDG_CONFIG_HOME", config_home)
+        .env("XDG_STATE_HOME", config_home.join("state"))
+        .env("XDG_RUNTIME_DIR", config_home.join("state"))
+        .args(["--no-journal"])
+        .args(args)
+        .stdout(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(status.success(), "cosmic-ctl {} failed", args.join(" "));
+
+    started.elapsed()
+}
+
+fn main() {
+    let sizes: Vec<usize> = env::var("COSMIC_CTL_BENCH_ENTRIES")
+        .unwrap_or_else(|_| "1000,10000".to_string())
+        .split(',')
+        .map(|size| {
+            size.trim()
+                .parse()
+                .expect("invalid COSMIC_CTL_BENCH_ENTRIES")
+        })
+        .collect();
+    let runs: u32 = env::var("COSMIC_CTL_BENCH_RUNS")
+        .map(|runs| runs.parse().expect("invalid COSMIC_CTL_BENCH_RUNS"))
+        .unwrap_or(5);
+
+    for entry_count in sizes {
+        let temp_dir = TempDir::new().unwrap();
+        let config_home = temp_dir.path();
+        generate_tree(config_home, entry_count);
+        let backup_file = config_home.join("backup.json");
+        let backup_file = backup_file.to_str().unwrap();
+
+        let commands: [(&str, Vec<&str>); 3] = [
+            (
+                "backup",
+                vec!["backup", "--xdg-dirs", "config", backup_file],
+            ),
+            ("search", vec!["search", "--xdg-dirs", "config", "name|7"]),
+            (
+                "reset (no matches)",
+                vec!["reset", "--xdg-dirs", "config", "--only", "none"],
+            ),
+        ];
+
+        for (name, args) in commands {
+            let total: Duration = (0..runs).map(|_| run(config_home, &args)).sum();
+            println!(
+                "{:>6} entries  {:<18} {:>10.2?} per run",
+                entry_count,
+                name,
+                total / runs
+            );
+        }
+    }
+}
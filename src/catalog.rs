@@ -0,0 +1,165 @@
+use ron::{Number, Value};
+use serde::Deserialize;
+use std::{
+    fmt,
+    io::{Error, ErrorKind},
+};
+
+/// The catalog of known components, embedded at build time.
+const CATALOG: &str = include_str!("../data/catalog.ron");
+
+#[derive(Deserialize)]
+pub struct Catalog {
+    pub components: Vec<ComponentInfo>,
+}
+
+#[derive(Deserialize)]
+pub struct ComponentInfo {
+    pub name: String,
+    pub description: String,
+    pub versions: Vec<VersionInfo>,
+}
+
+#[derive(Deserialize)]
+pub struct VersionInfo {
+    pub version: u64,
+    pub entries: Vec<EntryInfo>,
+}
+
+#[derive(Deserialize)]
+pub struct EntryInfo {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: EntryType,
+    /// The accepted variants of `Enum` entries.
+    #[serde(default)]
+    pub values: Vec<String>,
+    /// The RON value COSMIC ships with.
+    #[serde(default)]
+    pub default: Option<String>,
+    pub description: String,
+}
+
+/// The shape of an entry's RON value.
+#[derive(Clone, Copy, Deserialize, PartialEq, Eq, Debug)]
+pub enum EntryType {
+    Bool,
+    Integer,
+    Float,
+    String,
+    Enum,
+    Option,
+    List,
+    Map,
+    Struct,
+    Tuple,
+}
+
+impl fmt::Display for EntryType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+pub fn load_catalog() -> Result<Catalog, Error> {
+    ron::from_str(CATALOG).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Invalid component catalog: {}", e),
+        )
+    })
+}
+
+impl Catalog {
+    pub fn component(&self, name: &str) -> Option<&ComponentInfo> {
+        self.components
+            .iter()
+            .find(|component| component.name == name)
+    }
+
+    /// Describes why a value written to an entry looks wrong, if it does.
+    pub fn check_entry(
+        &self,
+        component: &str,
+        version: u64,
+        entry: &str,
+        value: Option<&str>,
+    ) -> Option<String> {
+        let Some(component_info) = self.component(component) else {
+            return Some(format!("Unknown component '{}'", component));
+        };
+        let Some(version_info) = component_info.version(version) else {
+            return Some(format!("Unknown version {}/v{}", component, version));
+        };
+        let Some(entry_info) = version_info.entry(entry) else {
+            return Some(format!(
+                "Unknown entry {}/v{}/{}",
+                component, version, entry
+            ));
+        };
+
+        let mismatch = entry_info.check_value(value?).err()?;
+        Some(format!(
+            "{}/v{}/{} expects {}: {}",
+            component, version, entry, entry_info.kind, mismatch
+        ))
+    }
+}
+
+impl ComponentInfo {
+    pub fn version(&self, version: u64) -> Option<&VersionInfo> {
+        self.versions.iter().find(|info| info.version == version)
+    }
+}
+
+impl VersionInfo {
+    pub fn entry(&self, name: &str) -> Option<&EntryInfo> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+}
+
+impl EntryInfo {
+    /// Checks that a RON value has the entry's type.
+    pub fn check_value(&self, value: &str) -> Result<(), String> {
+        let value = value.trim();
+        let parsed = || ron::from_str::<Value>(value).map_err(|e| format!("not valid RON ({})", e));
+
+        let matches = match self.kind {
+            EntryType::Enum => return self.check_variant(value),
+            EntryType::Bool => matches!(parsed()?, Value::Bool(_)),
+            EntryType::Integer => matches!(
+                parsed()?,
+                Value::Number(number) if !matches!(number, Number::F32(_) | Number::F64(_))
+            ),
+            EntryType::Float => matches!(parsed()?, Value::Number(_)),
+            EntryType::String => matches!(parsed()?, Value::String(_) | Value::Char(_)),
+            EntryType::Option => matches!(parsed()?, Value::Option(_)),
+            EntryType::List | EntryType::Tuple => matches!(parsed()?, Value::Seq(_)),
+            EntryType::Map | EntryType::Struct => matches!(parsed()?, Value::Map(_)),
+        };
+
+        if matches {
+            Ok(())
+        } else {
+            Err(format!("got '{}'", value))
+        }
+    }
+
+    /// Checks that a value is one of the allowed variants, which may carry data (e.g., 'Custom(2)').
+    fn check_variant(&self, value: &str) -> Result<(), String> {
+        let variant = value
+            .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .next()
+            .unwrap_or_default();
+
+        if self.values.iter().any(|allowed| allowed == variant) {
+            Ok(())
+        } else {
+            Err(format!(
+                "'{}' is not one of {}",
+                value,
+                self.values.join(", ")
+            ))
+        }
+    }
+}
@@ -1,11 +1,17 @@
 use crate::{
+    catalog::load_catalog,
     commands::Command,
     config::{
-        delete_configuration, delete_configuration_file, read_configuration,
-        read_configuration_file, write_configuration, write_configuration_file,
+        delete_configuration, delete_configuration_file, get_xdg_dir_path, normalize_path,
+        read_configuration, read_configuration_file, resolve_file_path, resolve_latest_version,
+        write_configuration, write_configuration_file, write_raw_configuration,
+        write_raw_configuration_file,
     },
     formats::FileFormat,
-    schema::{ConfigFile, EntryContent, Operation},
+    lock::lock_configuration,
+    metadata::verify_metadata,
+    schema::{ConfigFile, Entry, EntryContent, Operation, VersionSpec},
+    utils::{skip_reason, SkipSummary},
 };
 use clap::Args;
 use std::{
@@ -13,6 +19,7 @@ use std::{
     io::{Error, ErrorKind},
     path::PathBuf,
 };
+use unescaper::unescape;
 
 #[derive(Args)]
 pub struct ApplyCommand {
@@ -21,6 +28,78 @@ pub struct ApplyCommand {
     /// Print verbose output about skipped entries.
     #[arg(short, long)]
     pub verbose: bool,
+    /// Apply backups even if their checksum does not match.
+    #[arg(long)]
+    pub ignore_checksums: bool,
+    /// Overwrite read-only entries.
+    #[arg(short, long)]
+    pub force: bool,
+    /// Also allow file operations inside this directory (can be repeated).
+    #[arg(long)]
+    pub allow_path: Vec<PathBuf>,
+    /// Allow file operations on any path.
+    #[arg(long, conflicts_with = "allow_path")]
+    pub allow_any_path: bool,
+    /// Warn about entries that are not in the catalog or whose values don't match their types.
+    #[arg(long)]
+    pub check: bool,
+}
+
+impl ApplyCommand {
+    /// Directories file operations are confined to: the XDG config, data and state
+    /// directories, plus any given with `--allow-path`.
+    fn allowed_directories(&self) -> Result<Vec<PathBuf>, Error> {
+        let mut directories = Vec::new();
+
+        for xdg_dir in ["config", "data", "state"] {
+            if let Ok(path) = get_xdg_dir_path(xdg_dir) {
+                directories.push(normalize_path(&path)?);
+            }
+        }
+        for path in &self.allow_path {
+            directories.push(normalize_path(&resolve_file_path(path)?)?);
+        }
+
+        Ok(directories)
+    }
+
+    /// Checks every file operation before any of them runs.
+    fn validate_file_paths(&self, operations: &[Entry]) -> Result<(), Error> {
+        if self.allow_any_path {
+            return Ok(());
+        }
+
+        let allowed_directories = self.allowed_directories()?;
+        let violations: Vec<String> = operations
+            .iter()
+            .filter_map(|entry| entry.file.as_ref())
+            .filter_map(|file_path| {
+                match resolve_file_path(file_path).and_then(|p| normalize_path(&p)) {
+                    Ok(path) if allowed_directories.iter().any(|dir| path.starts_with(dir)) => None,
+                    Ok(path) => Some(format!(
+                        "{} is outside the allowed directories",
+                        path.display()
+                    )),
+                    Err(e) => Some(format!("{}: {}", file_path.display(), e)),
+                }
+            })
+            .collect();
+
+        if violations.is_empty() {
+            return Ok(());
+        }
+
+        for violation in &violations {
+            eprintln!("Error: {}", violation);
+        }
+        Err(Error::new(
+            ErrorKind::PermissionDenied,
+            format!(
+                "{} file operations are outside the XDG config, data and state directories, use --allow-path or --allow-any-path to permit them",
+                violations.len()
+            ),
+        ))
+    }
 }
 
 impl Command for ApplyCommand {
@@ -36,28 +115,43 @@ impl Command for ApplyCommand {
         let file_content = fs::read_to_string(&self.file)?;
         let config_file: ConfigFile = file_format.deserialize(&file_content)?;
 
+        if let Some(metadata) = &config_file.metadata {
+            verify_metadata(metadata, &config_file.operations, self.ignore_checksums)?;
+        }
+
+        self.validate_file_paths(&config_file.operations)?;
+        let _lock = lock_configuration()?;
+        let catalog = self.check.then(load_catalog).transpose()?;
+
         let mut delete_count = 0;
         let mut read_count = 0;
-        let mut skipped = 0;
+        let mut skipped = SkipSummary::default();
         let mut write_changes = 0;
 
         for entry in config_file.operations {
             if let Some(file_path) = &entry.file {
                 match entry.operation {
                     Operation::Write => {
-                        let value = entry.value.ok_or_else(|| {
+                        let value = entry.value.as_deref().ok_or_else(|| {
                             Error::new(
                                 ErrorKind::InvalidData,
                                 "Value is required for file write operations",
                             )
                         })?;
 
-                        match write_configuration_file(file_path, &value) {
+                        let result = match entry.encoding {
+                            Some(encoding) => encoding.decode(value).and_then(|bytes| {
+                                write_raw_configuration_file(file_path, &bytes, self.force)
+                            }),
+                            None => write_configuration_file(file_path, value, self.force),
+                        };
+
+                        match result {
                             Ok(false) => {
                                 if self.verbose {
                                     println!("Skipping {} - value unchanged", file_path.display());
                                 }
-                                skipped += 1;
+                                skipped.add("unchanged");
                             }
                             Ok(true) => {
                                 if self.verbose {
@@ -67,7 +161,7 @@ impl Command for ApplyCommand {
                             }
                             Err(e) => {
                                 eprintln!("Error writing {}: {}", file_path.display(), e);
-                                skipped += 1;
+                                skipped.add(skip_reason(&e));
                             }
                         }
                     }
@@ -80,7 +174,7 @@ impl Command for ApplyCommand {
                             if self.verbose {
                                 println!("Error reading {}: {}", file_path.display(), e);
                             }
-                            skipped += 1;
+                            skipped.add(skip_reason(&e));
                         }
                     },
                     Operation::Delete => match delete_configuration_file(file_path) {
@@ -94,7 +188,7 @@ impl Command for ApplyCommand {
                             if self.verbose {
                                 println!("Failed to delete {}: {}", file_path.display(), e);
                             }
-                            skipped += 1;
+                            skipped.add(skip_reason(&e));
                         }
                     },
                 }
@@ -107,13 +201,18 @@ impl Command for ApplyCommand {
                     "Component is required when file is not specified",
                 )
             })?;
-            let version = entry.version.as_ref().ok_or_else(|| {
-                Error::new(
-                    ErrorKind::InvalidData,
-                    "Version is required when file is not specified",
-                )
-            })?;
             let xdg_dir = entry.xdg_directory.as_deref().unwrap_or("config");
+            let version = &match entry.version {
+                Some(VersionSpec::Number(version)) => version,
+                Some(VersionSpec::Latest(_)) => resolve_latest_version(component, xdg_dir)?,
+                None => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        "Version is required when file is not specified",
+                    ))
+                }
+            };
+            let encoding = entry.encoding;
 
             let entries = entry.entries.ok_or_else(|| {
                 Error::new(
@@ -125,7 +224,31 @@ impl Command for ApplyCommand {
             match (entry.operation, entries) {
                 (Operation::Write, EntryContent::WriteEntries(entries)) => {
                     for (key, value) in entries {
-                        match write_configuration(component, version, &key, &value, xdg_dir) {
+                        if let Some(catalog) = &catalog {
+                            // Encoded values are not RON, only their entry names are checked.
+                            let ron_value = match encoding {
+                                Some(_) => None,
+                                None => Some(unescape(&value).unwrap_or_else(|_| value.clone())),
+                            };
+                            if let Some(warning) =
+                                catalog.check_entry(component, *version, &key, ron_value.as_deref())
+                            {
+                                eprintln!("Warning: {}", warning);
+                            }
+                        }
+
+                        let result = match encoding {
+                            Some(encoding) => encoding.decode(&value).and_then(|bytes| {
+                                write_raw_configuration(
+                                    component, version, &key, &bytes, xdg_dir, self.force,
+                                )
+                            }),
+                            None => write_configuration(
+                                component, version, &key, &value, xdg_dir, self.force,
+                            ),
+                        };
+
+                        match result {
                             Ok(false) => {
                                 if self.verbose {
                                     println!(
@@ -133,7 +256,7 @@ impl Command for ApplyCommand {
                                         component, version, key
                                     );
                                 }
-                                skipped += 1;
+                                skipped.add("unchanged");
                             }
                             Ok(true) => write_changes += 1,
                             Err(e) => {
@@ -141,7 +264,7 @@ impl Command for ApplyCommand {
                                     "Error writing {}/v{}/{}: {}",
                                     component, version, key, e
                                 );
-                                skipped += 1;
+                                skipped.add(skip_reason(&e));
                             }
                         }
                     }
@@ -157,7 +280,7 @@ impl Command for ApplyCommand {
                                 if self.verbose {
                                     println!("{}", e);
                                 }
-                                skipped += 1;
+                                skipped.add(skip_reason(&e));
                             }
                         }
                     }
@@ -178,7 +301,7 @@ impl Command for ApplyCommand {
                                         component, version, key, e
                                     );
                                 }
-                                skipped += 1;
+                                skipped.add(skip_reason(&e));
                             }
                         }
                     }
@@ -194,8 +317,14 @@ impl Command for ApplyCommand {
 
         println!(
             "Operations completed successfully. {} writes, {} reads, {} deletes, {} entries skipped.",
-            write_changes, read_count, delete_count, skipped
+            write_changes,
+            read_count,
+            delete_count,
+            skipped.total()
         );
+        if let Some(skipped) = skipped.describe() {
+            println!("{}", skipped);
+        }
         Ok(())
     }
 }
@@ -1,22 +1,22 @@
 use crate::{
     commands::Command,
-    config::{get_cosmic_configurations, parse_configuration_path, read_configuration},
+    config::{get_system_default_path, is_default_value, read_resolved, set_owner},
     formats::FileFormat,
-    schema::{ConfigFile, Entry, EntryContent, Operation},
+    metadata::create_metadata,
+    scan::{read_entries, scan_xdg_dirs, ScannedEntry},
+    schema::{ConfigFile, Encoding, Entry, EntryContent, Operation, VersionSpec, SCHEMA_URL},
+    utils::{PathFilter, SkipSummary},
 };
+use base64::{prelude::BASE64_STANDARD, Engine};
 use clap::Args;
-use std::{
-    collections::HashMap,
-    fs,
-    io::{Error, ErrorKind},
-    path::PathBuf,
-};
-use walkdir::WalkDir;
+use serde::Serialize;
+use std::{collections::BTreeMap, fs, io::Error, path::PathBuf, str};
 
 #[derive(Args)]
 pub struct BackupCommand {
-    /// Path to the output configuration file (supports JSON, TOML, RON).
-    pub file: PathBuf,
+    /// Path to the output configuration file (supports JSON, TOML, RON, Nix).
+    #[arg(required_unless_present = "report")]
+    pub file: Option<PathBuf>,
     /// Show which entries are being backed up.
     #[arg(short, long)]
     pub verbose: bool,
@@ -26,6 +26,21 @@ pub struct BackupCommand {
     /// Output format (auto-detected from file extension if not specified).
     #[arg(short, long)]
     pub format: Option<String>,
+    /// Patterns to include in the backup (comma-separated).
+    #[arg(long)]
+    pub include: Option<String>,
+    /// Patterns to exclude from the backup (comma-separated).
+    #[arg(long)]
+    pub exclude: Option<String>,
+    /// Only backup entries that differ from the system defaults.
+    #[arg(long)]
+    pub non_default: bool,
+    /// List customised entries and where their system defaults live.
+    #[arg(long, requires = "non_default")]
+    pub report: bool,
+    /// Omit the metadata block (timestamp, host, versions and checksum).
+    #[arg(long)]
+    pub no_metadata: bool,
 }
 
 impl Command for BackupCommand {
@@ -33,78 +48,166 @@ impl Command for BackupCommand {
 
     fn execute(&self) -> Result<(), Self::Err> {
         let file_format = match &self.format {
-            Some(fmt) => match fmt.to_lowercase().as_str() {
-                "json" => FileFormat::Json,
-                "toml" => FileFormat::Toml,
-                "ron" => FileFormat::Ron,
-                _ => {
-                    return Err(Error::new(
-                        ErrorKind::InvalidInput,
-                        format!("Unsupported format: {}", fmt),
-                    ))
-                }
+            Some(fmt) => FileFormat::from_name(fmt)?,
+            None => match &self.file {
+                Some(file) => FileFormat::from_path(file)?,
+                None => FileFormat::Json,
             },
-            None => FileFormat::from_path(&self.file)?,
         };
 
+        let filter = PathFilter::new(self.include.clone(), self.exclude.clone())?;
+
         if self.verbose {
             println!("Using {} format for output file", file_format.name());
         }
 
         let mut total_entry_count = 0;
+        let mut symlinked_entries = Vec::new();
+        let mut skipped = SkipSummary::default();
         let mut all_operations = Vec::new();
 
-        for xdg_dir in &self.xdg_dirs {
-            let cosmic_path = get_cosmic_configurations(xdg_dir)?;
-            let mut operations: HashMap<(String, u64), HashMap<String, String>> = HashMap::new();
+        for scanned in scan_xdg_dirs(&self.xdg_dirs, true)? {
+            let xdg_dir = &scanned.xdg_dir;
+            let mut operations: BTreeMap<
+                (String, u64, Option<Encoding>),
+                BTreeMap<String, String>,
+            > = BTreeMap::new();
             let mut entry_count = 0;
 
-            for entry in WalkDir::new(&cosmic_path)
-                .into_iter()
-                .filter_map(|e| e.ok())
-                .filter(|e| e.file_type().is_file())
-            {
-                if let Some((component, version, entry_name)) =
-                    parse_configuration_path(entry.path())
-                {
-                    match read_configuration(&component, &version, &entry_name, xdg_dir) {
-                        Ok(content) => {
-                            if self.verbose {
+            let included: Vec<&ScannedEntry> = scanned
+                .entries
+                .iter()
+                .flatten()
+                .filter(|entry| {
+                    let excluded = filter.is_excluded(&entry.relative_path);
+                    if excluded {
+                        if self.verbose {
+                            println!(
+                                "Skipping excluded path [{}]: {}",
+                                xdg_dir, entry.relative_path
+                            );
+                        }
+                        skipped.add("excluded");
+                    }
+                    !excluded
+                })
+                .collect();
+
+            for (entry, value) in read_entries(included) {
+                let ScannedEntry {
+                    component,
+                    version,
+                    entry_name,
+                    ..
+                } = entry;
+
+                match value {
+                    Ok(bytes) => {
+                        if self.non_default {
+                            let default_path =
+                                get_system_default_path(component, version, entry_name);
+                            let default_value = default_path
+                                .as_ref()
+                                .and_then(|path| read_resolved(path).ok());
+
+                            if let Some(default_value) = &default_value {
+                                let matches_default = *default_value == bytes
+                                    || matches!(
+                                        (str::from_utf8(&bytes), str::from_utf8(default_value)),
+                                        (Ok(content), Ok(default)) if is_default_value(content, default)
+                                    );
+
+                                if matches_default {
+                                    if self.verbose {
+                                        println!(
+                                            "Skipping [{}] {}/v{}/{} - matches system default",
+                                            xdg_dir, component, version, entry_name
+                                        );
+                                    }
+                                    skipped.add("matching system defaults");
+                                    continue;
+                                }
+                            }
+
+                            if self.report {
                                 println!(
-                                    "Backing up [{}]: {}/v{}/{}",
-                                    xdg_dir, component, version, entry_name
+                                    "[{}] {}/v{}/{} (default: {})",
+                                    xdg_dir,
+                                    component,
+                                    version,
+                                    entry_name,
+                                    default_path
+                                        .map(|path| path.display().to_string())
+                                        .unwrap_or_else(|| "none".to_string())
                                 );
                             }
+                        }
 
-                            operations
-                                .entry((component, version))
-                                .or_default()
-                                .insert(entry_name, content);
+                        if self.verbose {
+                            println!(
+                                "Backing up [{}]: {}/v{}/{}",
+                                xdg_dir, component, version, entry_name
+                            );
+                        }
 
-                            entry_count += 1;
+                        if let Some(target) = &entry.symlink_target {
+                            symlinked_entries.push(format!(
+                                "[{}] {} -> {}",
+                                xdg_dir,
+                                entry.relative_path,
+                                target.display()
+                            ));
                         }
-                        Err(e) => {
-                            if self.verbose {
-                                println!(
-                                    "Failed to backup [{}] {}/v{}/{}: {}",
-                                    xdg_dir, component, version, entry_name, e
-                                );
+
+                        // Entries that are not valid UTF-8 are stored as base64.
+                        let (content, encoding) = match String::from_utf8(bytes) {
+                            Ok(content) => (content, None),
+                            // Nix strings can't hold arbitrary bytes.
+                            Err(_) if file_format == FileFormat::Nix => {
+                                if self.verbose {
+                                    println!(
+                                        "Skipping [{}] {}/v{}/{} - not valid UTF-8",
+                                        xdg_dir, component, version, entry_name
+                                    );
+                                }
+                                skipped.add("not valid UTF-8");
+                                continue;
+                            }
+                            Err(e) => {
+                                (BASE64_STANDARD.encode(e.as_bytes()), Some(Encoding::Base64))
                             }
+                        };
+
+                        operations
+                            .entry((component.clone(), *version, encoding))
+                            .or_default()
+                            .insert(entry_name.clone(), content);
+
+                        entry_count += 1;
+                    }
+                    Err(e) => {
+                        if self.verbose {
+                            println!(
+                                "Failed to backup [{}] {}/v{}/{}: {}",
+                                xdg_dir, component, version, entry_name, e
+                            );
                         }
+                        skipped.add("unreadable");
                     }
                 }
             }
 
             let xdg_entries: Vec<Entry> = operations
                 .into_iter()
-                .map(|((component, version), entries)| Entry {
+                .map(|((component, version, encoding), entries)| Entry {
                     component: Some(component),
                     file: None,
                     value: None,
-                    version: Some(version),
+                    version: Some(VersionSpec::Number(version)),
                     operation: Operation::Write,
                     entries: Some(EntryContent::WriteEntries(entries)),
                     xdg_directory: Some(xdg_dir.to_string()),
+                    encoding,
                 })
                 .collect();
 
@@ -122,21 +225,104 @@ impl Command for BackupCommand {
         let backup_data = ConfigFile {
             // RON doesn't support JSON schemas
             schema: if file_format != FileFormat::Ron {
-                Some("https://raw.githubusercontent.com/cosmic-utils/cosmic-ctl/refs/heads/main/schema.json".to_string())
+                Some(SCHEMA_URL.to_string())
             } else {
                 None
             },
+            metadata: if self.no_metadata {
+                None
+            } else {
+                Some(create_metadata(&all_operations)?)
+            },
             operations: all_operations,
         };
 
-        let formatted_data = file_format.serialize(&backup_data)?;
-        fs::write(&self.file, formatted_data)?;
+        if !symlinked_entries.is_empty() {
+            println!(
+                "Backed up the target values of {} symlinked entries:",
+                symlinked_entries.len()
+            );
+            for symlinked_entry in &symlinked_entries {
+                println!("  {}", symlinked_entry);
+            }
+        }
+
+        let Some(file) = &self.file else {
+            println!("Found {} customised entries.", total_entry_count);
+            if let Some(skipped) = skipped.describe() {
+                println!("{}", skipped);
+            }
+            return Ok(());
+        };
+
+        let formatted_data = match file_format {
+            FileFormat::Nix => {
+                file_format.serialize(&nix_attribute_set(&backup_data.operations))?
+            }
+            _ => file_format.serialize(&backup_data)?,
+        };
+        fs::write(file, formatted_data)?;
+        set_owner(file)?;
 
         println!(
             "Backup completed successfully. {} total entries backed up in {} format.",
             total_entry_count,
             file_format.name()
         );
+        if let Some(skipped) = skipped.describe() {
+            println!("{}", skipped);
+        }
         Ok(())
     }
 }
+
+/// Entries keyed by component, 'vN' and entry name.
+type NixComponents<'a> = BTreeMap<&'a str, BTreeMap<String, BTreeMap<&'a str, &'a str>>>;
+
+/// What Nix backups contain: the config directory's entries as `component.vN.entry = value`
+/// at the top level, and those of the other XDG directories under `xdg.<dir>`.
+#[derive(Default, Serialize)]
+struct NixAttributeSet<'a> {
+    #[serde(flatten)]
+    config: NixComponents<'a>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    xdg: BTreeMap<&'a str, NixComponents<'a>>,
+}
+
+fn nix_attribute_set(operations: &[Entry]) -> NixAttributeSet<'_> {
+    let mut attributes = NixAttributeSet::default();
+
+    for operation in operations {
+        let (
+            Some(xdg_dir),
+            Some(component),
+            Some(VersionSpec::Number(version)),
+            Some(EntryContent::WriteEntries(entries)),
+        ) = (
+            &operation.xdg_directory,
+            &operation.component,
+            &operation.version,
+            &operation.entries,
+        )
+        else {
+            continue;
+        };
+
+        let components = match xdg_dir.as_str() {
+            "config" => &mut attributes.config,
+            xdg_dir => attributes.xdg.entry(xdg_dir).or_default(),
+        };
+        components
+            .entry(component)
+            .or_default()
+            .entry(format!("v{}", version))
+            .or_default()
+            .extend(
+                entries
+                    .iter()
+                    .map(|(name, value)| (name.as_str(), value.as_str())),
+            );
+    }
+
+    attributes
+}
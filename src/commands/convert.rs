@@ -0,0 +1,137 @@
+use crate::{
+    commands::Command,
+    formats::FileFormat,
+    schema::{ConfigFile, SCHEMA_URL},
+};
+use clap::Args;
+use serde_json::Value;
+use std::{
+    fs,
+    io::{Error, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+#[derive(Args)]
+pub struct ConvertCommand {
+    /// The apply file to convert (JSON, TOML or RON).
+    pub input: PathBuf,
+    /// Where to write the converted file.
+    pub output: PathBuf,
+    /// Format of the input file (auto-detected from file extension if not specified).
+    #[arg(long)]
+    pub from: Option<String>,
+    /// Format of the output file (auto-detected from file extension if not specified).
+    #[arg(long)]
+    pub to: Option<String>,
+}
+
+/// The format given on the command line, or the one of the file's extension.
+fn resolve_format(name: &Option<String>, path: &Path) -> Result<FileFormat, Error> {
+    match name {
+        Some(name) => FileFormat::from_name(name),
+        None => FileFormat::from_path(path),
+    }
+}
+
+/// Collects the fields of `input` that did not survive parsing into `parsed`.
+fn collect_dropped_fields(input: &Value, parsed: &Value, path: &str, dropped: &mut Vec<String>) {
+    match (input, parsed) {
+        (Value::Object(input), Value::Object(parsed)) => {
+            for (name, value) in input {
+                let field_path = match path {
+                    "" => name.clone(),
+                    _ => format!("{}.{}", path, name),
+                };
+                match parsed.get(name) {
+                    Some(parsed) => collect_dropped_fields(value, parsed, &field_path, dropped),
+                    // Null fields are the same as missing ones.
+                    None if !value.is_null() => dropped.push(field_path),
+                    None => {}
+                }
+            }
+        }
+        (Value::Array(input), Value::Array(parsed)) => {
+            for (index, (value, parsed)) in input.iter().zip(parsed).enumerate() {
+                collect_dropped_fields(value, parsed, &format!("{}[{}]", path, index), dropped);
+            }
+        }
+        _ => {}
+    }
+}
+
+impl Command for ConvertCommand {
+    type Err = Error;
+
+    fn execute(&self) -> Result<(), Self::Err> {
+        let input_format = resolve_format(&self.from, &self.input)?;
+        let output_format = resolve_format(&self.to, &self.output)?;
+        if output_format == FileFormat::Nix {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Nix files can't be applied, use 'backup --format nix' to write one",
+            ));
+        }
+
+        let data = fs::read_to_string(&self.input)?;
+        let mut config: ConfigFile = input_format.deserialize(&data)?;
+
+        let mut lossy = Vec::new();
+        if let Ok(input) = input_format.deserialize::<Value>(&data) {
+            let mut dropped = Vec::new();
+            collect_dropped_fields(&input, &to_value(&config)?, "", &mut dropped);
+            lossy.extend(
+                dropped
+                    .into_iter()
+                    .map(|field| format!("dropped unknown field {}", field)),
+            );
+        }
+
+        // RON doesn't support JSON schemas
+        if output_format == FileFormat::Ron {
+            if config.schema.take().is_some() {
+                lossy.push(format!(
+                    "dropped $schema, {} files can't reference a JSON schema",
+                    output_format.name()
+                ));
+            }
+        } else if config.schema.is_none() {
+            config.schema = Some(SCHEMA_URL.to_string());
+        }
+
+        let converted = output_format.serialize(&config)?;
+        let read_back: ConfigFile = output_format.deserialize(&converted).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "The converted {} file could not be read back: {}",
+                    output_format.name(),
+                    e
+                ),
+            )
+        })?;
+        if to_value(&read_back)? != to_value(&config)? {
+            lossy.push(format!(
+                "some values read back differently from the {} file",
+                output_format.name()
+            ));
+        }
+
+        fs::write(&self.output, converted)?;
+
+        for loss in &lossy {
+            eprintln!("Warning: {}", loss);
+        }
+        println!(
+            "Converted {} ({}) to {} ({}).",
+            self.input.display(),
+            input_format.name(),
+            self.output.display(),
+            output_format.name()
+        );
+        Ok(())
+    }
+}
+
+fn to_value(config: &ConfigFile) -> Result<Value, Error> {
+    serde_json::to_value(config).map_err(|e| Error::new(ErrorKind::InvalidData, e))
+}
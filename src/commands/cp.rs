@@ -0,0 +1,386 @@
+use crate::{
+    commands::Command,
+    config::{
+        delete_configuration, delete_user_configuration, get_cosmic_configurations,
+        list_configuration_entries, read_raw_configuration, read_raw_user_configuration,
+        user_path_overrides, validate_component_name, validate_entry_name, write_raw_configuration,
+        write_raw_user_configuration, PathOverrides,
+    },
+    lock::lock_configuration,
+    utils::SkipSummary,
+};
+use clap::Args;
+use glob::Pattern;
+use std::{
+    fmt,
+    io::{Error, ErrorKind},
+    rc::Rc,
+};
+
+#[derive(Args)]
+pub struct CpCommand {
+    /// The entries to copy, as '[user@][xdg:]component/vN/entry' (e.g., 'state:com.system76.CosmicPanel.Panel/v1/*').
+    pub source: String,
+    /// Where to copy them, as '[user@][xdg:]component/vN[/entry]' (e.g., 'alice@com.system76.CosmicPanel.Dock/v1').
+    pub destination: String,
+    /// Replace entries that already have a different value.
+    #[arg(long, conflicts_with = "no_clobber")]
+    pub overwrite: bool,
+    /// Skip entries that already exist.
+    #[arg(short, long)]
+    pub no_clobber: bool,
+    /// Overwrite read-only entries.
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+/// The location of an entry, or of a component version when `entry` is `None`.
+struct Address {
+    /// The user whose configuration the address is in, when it isn't the current one.
+    user: Option<(String, Rc<PathOverrides>)>,
+    xdg_dir: String,
+    component: String,
+    version: u64,
+    entry: Option<String>,
+}
+
+impl PartialEq for Address {
+    fn eq(&self, other: &Self) -> bool {
+        self.user_name() == other.user_name()
+            && self.xdg_dir == other.xdg_dir
+            && self.component == other.component
+            && self.version == other.version
+            && self.entry == other.entry
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.user_name() {
+            Some(user) => write!(f, "[{}@{}]", user, self.xdg_dir)?,
+            None => write!(f, "[{}]", self.xdg_dir)?,
+        }
+        write!(f, " {}/v{}", self.component, self.version)?;
+        if let Some(entry) = &self.entry {
+            write!(f, "/{}", entry)?;
+        }
+        Ok(())
+    }
+}
+
+impl Address {
+    /// Parses '[user@][xdg:]component/vN[/entry]', defaulting to the current user's `config`
+    /// XDG directory.
+    fn parse(address: &str) -> Result<Self, Error> {
+        let invalid = || {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Invalid address '{}': expected '[user@][xdg:]component/vN/entry'",
+                    address
+                ),
+            )
+        };
+
+        let (user, path) = match address.split_once('@') {
+            Some((user, path)) if !user.is_empty() && !user.contains(['/', ':']) => {
+                (Some(user.to_string()), path)
+            }
+            _ => (None, address),
+        };
+        let (xdg_dir, path) = match path.split_once(':') {
+            Some((xdg_dir, path)) => (xdg_dir.to_string(), path),
+            None => ("config".to_string(), path),
+        };
+
+        let mut parts = path.split('/');
+        let component = parts.next().ok_or_else(invalid)?.to_string();
+        let version = parts
+            .next()
+            .and_then(|version| version.strip_prefix('v'))
+            .and_then(|version| version.parse().ok())
+            .ok_or_else(invalid)?;
+        let entry = parts
+            .next()
+            .filter(|entry| !entry.is_empty())
+            .map(str::to_string);
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+
+        validate_component_name(&component)?;
+        let user = match user {
+            Some(user) => {
+                let overrides = user_path_overrides(&user)?;
+                Some((user, Rc::new(overrides)))
+            }
+            None => None,
+        };
+
+        Ok(Address {
+            user,
+            xdg_dir,
+            component,
+            version,
+            entry,
+        })
+    }
+
+    fn user_name(&self) -> Option<&str> {
+        self.user.as_ref().map(|(user, _)| user.as_str())
+    }
+
+    fn with_entry(&self, entry: &str) -> Self {
+        Address {
+            user: self.user.clone(),
+            xdg_dir: self.xdg_dir.clone(),
+            component: self.component.clone(),
+            version: self.version,
+            entry: Some(entry.to_string()),
+        }
+    }
+
+    fn entry(&self) -> &str {
+        self.entry.as_deref().unwrap_or_default()
+    }
+
+    fn read(&self) -> Result<Vec<u8>, Error> {
+        match &self.user {
+            Some((_, overrides)) => read_raw_user_configuration(
+                overrides,
+                &self.component,
+                &self.version,
+                self.entry(),
+                &self.xdg_dir,
+            ),
+            None => {
+                read_raw_configuration(&self.component, &self.version, self.entry(), &self.xdg_dir)
+            }
+        }
+    }
+
+    fn write(&self, value: &[u8], force: bool) -> Result<bool, Error> {
+        match &self.user {
+            Some((_, overrides)) => write_raw_user_configuration(
+                overrides,
+                &self.component,
+                &self.version,
+                self.entry(),
+                value,
+                &self.xdg_dir,
+                force,
+            ),
+            None => write_raw_configuration(
+                &self.component,
+                &self.version,
+                self.entry(),
+                value,
+                &self.xdg_dir,
+                force,
+            ),
+        }
+    }
+
+    fn delete(&self) -> Result<(), Error> {
+        match &self.user {
+            Some((_, overrides)) => delete_user_configuration(
+                overrides,
+                &self.component,
+                &self.version,
+                self.entry(),
+                &self.xdg_dir,
+            ),
+            None => {
+                delete_configuration(&self.component, &self.version, self.entry(), &self.xdg_dir)
+            }
+        }
+    }
+
+    /// The entries this address matches, treating the entry as a glob pattern.
+    fn matching_entries(&self) -> Result<Vec<Address>, Error> {
+        let Some(entry) = &self.entry else {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "{} does not name an entry, use '{}/*' to copy all of them",
+                    self, self
+                ),
+            ));
+        };
+        let pattern = Pattern::new(entry).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("Invalid entry pattern '{}': {}", entry, e),
+            )
+        })?;
+
+        let cosmic_dir = match &self.user {
+            Some((_, overrides)) => overrides.xdg_dir_path(&self.xdg_dir)?.join("cosmic"),
+            None => get_cosmic_configurations(&self.xdg_dir)?,
+        };
+        let entries = list_configuration_entries(&cosmic_dir)
+            .remove(&self.component)
+            .and_then(|mut versions| versions.remove(&self.version))
+            .unwrap_or_default();
+
+        Ok(entries
+            .iter()
+            .filter(|name| pattern.matches(name))
+            .map(|name| self.with_entry(name))
+            .collect())
+    }
+}
+
+/// An entry to copy, with the value its target held before.
+struct Transfer {
+    source: Address,
+    target: Address,
+    value: Vec<u8>,
+    previous: Option<Vec<u8>>,
+}
+
+/// Puts back what the targets held before they were written, after a later write failed.
+fn restore_targets(written: &[&Transfer]) {
+    for transfer in written.iter().rev() {
+        // These writes replace files cosmic-ctl just wrote, so read-only modes don't apply.
+        let restored = match &transfer.previous {
+            Some(previous) => transfer.target.write(previous, true).map(|_| ()),
+            None => transfer.target.delete(),
+        };
+        if let Err(e) = restored {
+            eprintln!("Warning: failed to restore {}: {}", transfer.target, e);
+        }
+    }
+}
+
+impl CpCommand {
+    /// Copies the matching entries, deleting the sources afterwards when `remove_source` is set.
+    /// Everything is read and checked before the first write, and the sources are only deleted
+    /// once every entry has been written.
+    pub(crate) fn transfer(&self, remove_source: bool) -> Result<(), Error> {
+        let source = Address::parse(&self.source)?;
+        let destination = Address::parse(&self.destination)?;
+        let is_pattern = source
+            .entry
+            .as_deref()
+            .is_some_and(|entry| Pattern::escape(entry) != entry);
+
+        if let Some(entry) = &destination.entry {
+            if is_pattern {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "The destination must be a component version (e.g., '{}/v{}') when the source is a pattern",
+                        destination.component, destination.version
+                    ),
+                ));
+            }
+            validate_entry_name(entry)?;
+        }
+
+        let _lock = lock_configuration()?;
+        let sources = source.matching_entries()?;
+        if sources.is_empty() {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("No entries match {}", source),
+            ));
+        }
+
+        let mut transfers = Vec::new();
+        let mut conflicts = Vec::new();
+        let mut skipped = SkipSummary::default();
+
+        for source in sources {
+            let target =
+                destination.with_entry(destination.entry.as_deref().unwrap_or(source.entry()));
+
+            if source == target {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("{} cannot be copied onto itself", source),
+                ));
+            }
+
+            let value = source.read()?;
+            let previous = match target.read() {
+                Ok(existing) => Some(existing),
+                Err(e) if e.kind() == ErrorKind::NotFound => None,
+                Err(e) => return Err(e),
+            };
+
+            match previous {
+                // Moving onto an identical value still has to remove the source.
+                Some(ref existing) if *existing == value && !remove_source => {
+                    skipped.add("unchanged");
+                    continue;
+                }
+                Some(ref existing) if *existing != value && self.no_clobber => {
+                    skipped.add("already existing");
+                    continue;
+                }
+                Some(ref existing) if *existing != value && !self.overwrite => {
+                    conflicts.push(target);
+                    continue;
+                }
+                _ => {}
+            }
+
+            transfers.push(Transfer {
+                source,
+                target,
+                value,
+                previous,
+            });
+        }
+
+        if !conflicts.is_empty() {
+            for conflict in &conflicts {
+                eprintln!("Error: {} already exists with a different value", conflict);
+            }
+            return Err(Error::new(
+                ErrorKind::AlreadyExists,
+                format!(
+                    "{} entries already exist, use --overwrite to replace them or --no-clobber to skip them",
+                    conflicts.len()
+                ),
+            ));
+        }
+
+        let mut written = Vec::new();
+        for transfer in &transfers {
+            if transfer.previous.as_ref() == Some(&transfer.value) {
+                continue;
+            }
+            if let Err(e) = transfer.target.write(&transfer.value, self.force) {
+                restore_targets(&written);
+                return Err(e);
+            }
+            written.push(transfer);
+        }
+
+        if remove_source {
+            for transfer in &transfers {
+                transfer.source.delete()?;
+            }
+        }
+
+        let verb = if remove_source { "Moved" } else { "Copied" };
+        for transfer in &transfers {
+            println!("{} {} to {}", verb, transfer.source, transfer.target);
+        }
+        println!("{} {} entries.", verb, transfers.len());
+        if let Some(skipped) = skipped.describe() {
+            println!("{}", skipped);
+        }
+        Ok(())
+    }
+}
+
+impl Command for CpCommand {
+    type Err = Error;
+
+    fn execute(&self) -> Result<(), Self::Err> {
+        self.transfer(false)
+    }
+}
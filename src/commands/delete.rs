@@ -1,15 +1,15 @@
 use crate::{
     commands::Command,
-    config::{delete_configuration, delete_configuration_file},
+    config::{delete_configuration, delete_configuration_file, resolve_latest_version},
 };
 use clap::Args;
 use std::{io::Error, path::PathBuf};
 
 #[derive(Args)]
 pub struct DeleteCommand {
-    /// The configuration version of the component.
-    #[arg(short, long, default_value_t = 1)]
-    pub version: u64,
+    /// The configuration version of the component (defaults to the latest one present).
+    #[arg(short, long)]
+    pub version: Option<u64>,
     /// The component to configure (e.g., 'com.system76.CosmicComp').
     #[arg(short, long, required_unless_present = "file")]
     pub component: Option<String>,
@@ -40,9 +40,15 @@ impl Command for DeleteCommand {
                 }
             }
         } else {
+            let component = self.component.as_ref().unwrap();
+            let version = match self.version {
+                Some(version) => version,
+                None => resolve_latest_version(component, &self.xdg_dir)?,
+            };
+
             match delete_configuration(
-                self.component.as_ref().unwrap(),
-                &self.version,
+                component,
+                &version,
                 self.entry.as_ref().unwrap(),
                 &self.xdg_dir,
             ) {
@@ -0,0 +1,103 @@
+use crate::{
+    catalog::{load_catalog, EntryInfo},
+    commands::Command,
+};
+use clap::Args;
+use std::io::{Error, ErrorKind};
+
+#[derive(Args)]
+pub struct DescribeCommand {
+    /// The component to describe (e.g., 'com.system76.CosmicComp').
+    #[arg(short, long)]
+    pub component: String,
+    /// Only describe this entry (e.g., 'autotile_behavior').
+    #[arg(short, long)]
+    pub entry: Option<String>,
+    /// Only describe this configuration version of the component.
+    #[arg(short, long)]
+    pub version: Option<u64>,
+}
+
+impl Command for DescribeCommand {
+    type Err = Error;
+
+    fn execute(&self) -> Result<(), Self::Err> {
+        let catalog = load_catalog()?;
+        let component = catalog.component(&self.component).ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                format!("Component '{}' is not in the catalog", self.component),
+            )
+        })?;
+
+        let versions: Vec<_> = component
+            .versions
+            .iter()
+            .filter(|info| self.version.is_none_or(|version| info.version == version))
+            .collect();
+        if versions.is_empty() {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!(
+                    "Version {} of '{}' is not in the catalog",
+                    self.version.unwrap_or_default(),
+                    self.component
+                ),
+            ));
+        }
+
+        if let Some(entry_name) = &self.entry {
+            let entries: Vec<_> = versions
+                .iter()
+                .filter_map(|info| Some((info.version, info.entry(entry_name)?)))
+                .collect();
+            if entries.is_empty() {
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    format!(
+                        "Entry '{}' of '{}' is not in the catalog",
+                        entry_name, self.component
+                    ),
+                ));
+            }
+
+            for (version, entry) in entries {
+                println!("{}/v{}/{}", component.name, version, entry.name);
+                println!("  Type: {}", entry.kind);
+                if !entry.values.is_empty() {
+                    println!("  Values: {}", entry.values.join(", "));
+                }
+                if let Some(default) = &entry.default {
+                    println!("  Default: {}", default);
+                }
+                println!("  {}", entry.description);
+            }
+            return Ok(());
+        }
+
+        println!("{}", component.name);
+        println!("  {}", component.description);
+        for info in versions {
+            println!();
+            println!("v{}:", info.version);
+            for entry in &info.entries {
+                println!("  {} ({})", entry.name, summarize(entry));
+                println!("    {}", entry.description);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The type, allowed values and default of an entry on one line.
+fn summarize(entry: &EntryInfo) -> String {
+    let mut summary = entry.kind.to_string();
+    if !entry.values.is_empty() {
+        summary.push_str(&format!(": {}", entry.values.join(" | ")));
+    }
+    if let Some(default) = &entry.default {
+        summary.push_str(&format!(", default: {}", default));
+    }
+    summary
+}
@@ -0,0 +1,235 @@
+use crate::{
+    commands::Command,
+    config::{
+        get_system_default_path, read_configuration, read_configuration_file,
+        resolve_latest_version, write_raw_configuration, write_raw_configuration_file,
+    },
+};
+use clap::Args;
+use similar::TextDiff;
+use std::{
+    env, fs,
+    io::{stdin, stdout, Error, Seek, Write},
+    path::{Path, PathBuf},
+    process,
+};
+use tempfile::NamedTempFile;
+
+/// Prefix of the lines cosmic-ctl adds to the edited file, removed before validating it.
+const ERROR_PREFIX: &str = "// cosmic-ctl: ";
+
+#[derive(Args)]
+pub struct EditCommand {
+    /// The configuration version of the component (defaults to the latest one present).
+    #[arg(short, long)]
+    pub version: Option<u64>,
+    /// The component to configure (e.g., 'com.system76.CosmicComp').
+    #[arg(short, long, required_unless_present = "file")]
+    pub component: Option<String>,
+    /// The specific configuration entry to modify (e.g., 'autotile').
+    #[arg(short, long, required_unless_present = "file")]
+    pub entry: Option<String>,
+    /// The XDG directory to use (e.g., 'config', 'cache', 'data').
+    #[arg(short, long, default_value = "config")]
+    pub xdg_dir: String,
+    /// Direct path to the configuration file.
+    #[arg(long, required_unless_present_all = &["component", "entry"])]
+    pub file: Option<PathBuf>,
+    /// Write the changes without asking for confirmation.
+    #[arg(short, long)]
+    pub yes: bool,
+    /// Overwrite read-only entries.
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+impl EditCommand {
+    /// The version of the entry to edit, or `None` when editing a file.
+    fn resolve_version(&self) -> Result<Option<u64>, Error> {
+        if self.file.is_some() {
+            return Ok(None);
+        }
+
+        match self.version {
+            Some(version) => Ok(Some(version)),
+            None => {
+                resolve_latest_version(self.component.as_ref().unwrap(), &self.xdg_dir).map(Some)
+            }
+        }
+    }
+
+    /// The current value, falling back to the system default for component entries.
+    fn read_current_value(&self, version: Option<u64>) -> Option<String> {
+        let Some(version) = version else {
+            return read_configuration_file(self.file.as_ref()?).ok();
+        };
+
+        let component = self.component.as_ref().unwrap();
+        let entry = self.entry.as_ref().unwrap();
+
+        read_configuration(component, &version, entry, &self.xdg_dir)
+            .ok()
+            .or_else(|| {
+                let default_path = get_system_default_path(component, &version, entry)?;
+                fs::read_to_string(default_path).ok()
+            })
+    }
+
+    fn write_value(&self, version: Option<u64>, value: &str) -> Result<bool, Error> {
+        match version {
+            Some(version) => write_raw_configuration(
+                self.component.as_ref().unwrap(),
+                &version,
+                self.entry.as_ref().unwrap(),
+                value.as_bytes(),
+                &self.xdg_dir,
+                self.force,
+            ),
+            None => write_raw_configuration_file(
+                self.file.as_ref().unwrap(),
+                value.as_bytes(),
+                self.force,
+            ),
+        }
+    }
+
+    /// Creates the file to edit with a random name, only readable by the current user.
+    fn create_edit_file(&self) -> Result<NamedTempFile, Error> {
+        let name = match &self.file {
+            Some(file_path) => file_path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            None => self.entry.clone().unwrap_or_default(),
+        };
+
+        tempfile::Builder::new()
+            .prefix("cosmic-ctl-")
+            .suffix(&format!("-{}.ron", name))
+            .tempfile()
+    }
+
+    /// Opens the value in the editor until it is valid RON, returning the edited value,
+    /// or `None` when the editor leaves the buffer unchanged or empty.
+    fn edit_until_valid(&self, original: &str) -> Result<Option<String>, Error> {
+        let mut edit_file = self.create_edit_file()?;
+        let mut contents = original.to_string();
+        let mut error: Option<String> = None;
+
+        loop {
+            let header = error
+                .iter()
+                .flat_map(|error| error.lines())
+                .map(|line| format!("{}{}\n", ERROR_PREFIX, line))
+                .collect::<String>();
+            let file = edit_file.as_file_mut();
+            file.set_len(0)?;
+            file.rewind()?;
+            file.write_all(format!("{}{}", header, contents).as_bytes())?;
+            file.flush()?;
+
+            open_editor(edit_file.path())?;
+            // Editors may replace the file instead of writing to it, so read it by path.
+            let edited = strip_error_lines(&fs::read_to_string(edit_file.path())?);
+
+            if edited.trim().is_empty() || edited == contents {
+                return Ok(None);
+            }
+            contents = edited;
+
+            match ron::from_str::<ron::Value>(&contents) {
+                Ok(_) => return Ok(Some(contents)),
+                Err(e) => error = Some(format!("Invalid RON: {}", e)),
+            }
+        }
+    }
+}
+
+impl Command for EditCommand {
+    type Err = Error;
+
+    fn execute(&self) -> Result<(), Self::Err> {
+        let version = self.resolve_version()?;
+        let current = self.read_current_value(version).unwrap_or_default();
+        let original = current.strip_suffix('\n').unwrap_or(&current);
+        let Some(edited) = self
+            .edit_until_valid(original)?
+            .filter(|edited| edited != original)
+        else {
+            println!("Doing nothing. No changes were made.");
+            return Ok(());
+        };
+
+        print!(
+            "{}",
+            TextDiff::from_lines(original, &edited)
+                .unified_diff()
+                .header("current", "edited")
+        );
+
+        if !self.yes {
+            print!("Write these changes? [y/N] ");
+            stdout().flush()?;
+
+            let mut response = String::new();
+            stdin().read_line(&mut response)?;
+
+            if !response.trim().eq_ignore_ascii_case("y") {
+                println!("Operation cancelled.");
+                return Ok(());
+            }
+        }
+
+        match self.write_value(version, &edited) {
+            Ok(_) => {
+                println!("Configuration entry written successfully.");
+                Ok(())
+            }
+            Err(e) => {
+                eprintln!("Error writing configuration entry: {}", e);
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Runs `$VISUAL`, then `$EDITOR`, then `vi` on the file through the shell, so editors with arguments work.
+fn open_editor(path: &Path) -> Result<(), Error> {
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+
+    let status = process::Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$1\"", editor))
+        .arg("sh")
+        .arg(path)
+        .status()
+        .map_err(|e| {
+            Error::new(
+                e.kind(),
+                format!("Failed to run editor '{}': {}", editor, e),
+            )
+        })?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(Error::other(format!(
+            "Editor '{}' exited with {}",
+            editor, status
+        )))
+    }
+}
+
+/// Removes the error lines added at the top of the file and the editor's trailing newline.
+fn strip_error_lines(contents: &str) -> String {
+    let value = contents
+        .split_inclusive('\n')
+        .skip_while(|line| line.starts_with(ERROR_PREFIX))
+        .collect::<String>();
+
+    value.strip_suffix('\n').unwrap_or(&value).to_string()
+}
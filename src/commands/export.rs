@@ -0,0 +1,104 @@
+use crate::{
+    commands::Command,
+    config::{create_dir_all_owned, set_owner},
+    scan::{read_entries, scan_xdg_dirs, ScannedEntry},
+    utils::{PathFilter, SkipSummary},
+};
+use clap::Args;
+use std::{fs, io::Error, path::PathBuf};
+
+#[derive(Args)]
+pub struct ExportCommand {
+    /// Directory to write the entries to, as '<dir>/<xdg>/cosmic/<component>/vN/<entry>' files.
+    #[arg(long)]
+    pub tree: PathBuf,
+    /// Show which entries are being exported.
+    #[arg(short, long)]
+    pub verbose: bool,
+    /// The XDG directories to export (comma-separated) (e.g., 'config,cache,data').
+    #[arg(short, long, value_delimiter = ',', default_value = "config,state")]
+    pub xdg_dirs: Vec<String>,
+    /// Patterns to include in the export (comma-separated).
+    #[arg(long)]
+    pub include: Option<String>,
+    /// Patterns to exclude from the export (comma-separated).
+    #[arg(long)]
+    pub exclude: Option<String>,
+}
+
+impl Command for ExportCommand {
+    type Err = Error;
+
+    fn execute(&self) -> Result<(), Self::Err> {
+        let filter = PathFilter::new(self.include.clone(), self.exclude.clone())?;
+
+        let mut exported_count = 0;
+        let mut skipped = SkipSummary::default();
+
+        for scanned in scan_xdg_dirs(&self.xdg_dirs, true)? {
+            let xdg_dir = &scanned.xdg_dir;
+            let included: Vec<&ScannedEntry> = scanned
+                .entries
+                .iter()
+                .flatten()
+                .filter(|entry| {
+                    let excluded = filter.is_excluded(&entry.relative_path);
+                    if excluded {
+                        if self.verbose {
+                            println!(
+                                "Skipping excluded path [{}]: {}",
+                                xdg_dir, entry.relative_path
+                            );
+                        }
+                        skipped.add("excluded");
+                    }
+                    !excluded
+                })
+                .collect();
+
+            for (entry, value) in read_entries(included) {
+                let bytes = match value {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        if self.verbose {
+                            println!(
+                                "Failed to export [{}] {}: {}",
+                                xdg_dir, entry.relative_path, e
+                            );
+                        }
+                        skipped.add("unreadable");
+                        continue;
+                    }
+                };
+
+                let path = self
+                    .tree
+                    .join(xdg_dir)
+                    .join("cosmic")
+                    .join(&entry.component)
+                    .join(format!("v{}", entry.version))
+                    .join(&entry.entry_name);
+                if let Some(parent) = path.parent() {
+                    create_dir_all_owned(parent)?;
+                }
+                fs::write(&path, bytes)?;
+                set_owner(&path)?;
+
+                if self.verbose {
+                    println!("Exported [{}]: {}", xdg_dir, entry.relative_path);
+                }
+                exported_count += 1;
+            }
+        }
+
+        println!(
+            "Exported {} entries to {}.",
+            exported_count,
+            self.tree.display()
+        );
+        if let Some(skipped) = skipped.describe() {
+            println!("{}", skipped);
+        }
+        Ok(())
+    }
+}
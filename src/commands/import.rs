@@ -0,0 +1,105 @@
+use crate::{
+    commands::Command,
+    config::{parse_configuration_path, write_raw_configuration},
+    lock::lock_configuration,
+    utils::{skip_reason, SkipSummary},
+};
+use clap::Args;
+use std::{
+    fs,
+    io::{Error, ErrorKind},
+    path::{Component, PathBuf},
+};
+use walkdir::WalkDir;
+
+#[derive(Args)]
+pub struct ImportCommand {
+    /// Directory of '<xdg>/cosmic/<component>/vN/<entry>' files, as written by 'export --tree'.
+    #[arg(long)]
+    pub tree: PathBuf,
+    /// Show what happens to each entry.
+    #[arg(short, long)]
+    pub verbose: bool,
+    /// Overwrite read-only entries.
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+impl Command for ImportCommand {
+    type Err = Error;
+
+    fn execute(&self) -> Result<(), Self::Err> {
+        if !self.tree.is_dir() {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("{} is not a directory", self.tree.display()),
+            ));
+        }
+
+        let _lock = lock_configuration()?;
+
+        let mut write_changes = 0;
+        let mut skipped = SkipSummary::default();
+
+        for file in WalkDir::new(&self.tree)
+            .follow_links(true)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+        {
+            let relative_path = file.path().strip_prefix(&self.tree).unwrap_or(file.path());
+            let xdg_dir = match relative_path.components().collect::<Vec<_>>().as_slice() {
+                [Component::Normal(xdg_dir), Component::Normal(cosmic), _, _, _]
+                    if *cosmic == "cosmic" =>
+                {
+                    xdg_dir.to_str()
+                }
+                _ => None,
+            };
+
+            let (Some(xdg_dir), Some((component, version, entry))) =
+                (xdg_dir, parse_configuration_path(relative_path))
+            else {
+                if self.verbose {
+                    println!(
+                        "Skipping {} - not an <xdg>/cosmic/<component>/vN/<entry> file",
+                        relative_path.display()
+                    );
+                }
+                skipped.add("unrecognised");
+                continue;
+            };
+
+            let address = format!("[{}] {}/v{}/{}", xdg_dir, component, version, entry);
+            let result = fs::read(file.path()).and_then(|value| {
+                write_raw_configuration(&component, &version, &entry, &value, xdg_dir, self.force)
+            });
+
+            match result {
+                Ok(true) => {
+                    if self.verbose {
+                        println!("Wrote {}", address);
+                    }
+                    write_changes += 1;
+                }
+                Ok(false) => {
+                    if self.verbose {
+                        println!("Skipping {} - value unchanged", address);
+                    }
+                    skipped.add("unchanged");
+                }
+                Err(e) => {
+                    eprintln!("Error importing {}: {}", address, e);
+                    skipped.add(skip_reason(&e));
+                }
+            }
+        }
+
+        println!("Import completed. {} entries written.", write_changes);
+        if let Some(skipped) = skipped.describe() {
+            println!("{}", skipped);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+use crate::{
+    commands::Command,
+    journal::{read_journal, JournalRecord},
+};
+use clap::Args;
+use glob::Pattern;
+use std::{
+    io::{Error, ErrorKind},
+    time::{Duration, SystemTime},
+};
+
+#[derive(Args)]
+pub struct LogCommand {
+    /// Only show changes to components matching this pattern (e.g., 'com.system76.CosmicPanel*').
+    #[arg(short, long)]
+    pub component: Option<String>,
+    /// Only show changes since this time (e.g., '2025-04-12', '2025-04-12T10:00:00Z' or '2h').
+    #[arg(short, long)]
+    pub since: Option<String>,
+}
+
+/// Parses a timestamp or a duration relative to now.
+fn parse_since(since: &str) -> Result<SystemTime, Error> {
+    if let Ok(duration) = humantime::parse_duration(since) {
+        return SystemTime::now()
+            .checked_sub(duration)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "Duration is too large"));
+    }
+
+    let timestamp = if since.len() == 10 {
+        format!("{}T00:00:00Z", since)
+    } else {
+        since.to_string()
+    };
+
+    humantime::parse_rfc3339_weak(&timestamp).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("Invalid time '{}': {}", since, e),
+        )
+    })
+}
+
+fn format_value(value: &Option<String>) -> String {
+    match value {
+        Some(value) => format!("\"{}\"", value.escape_debug()),
+        None => "(none)".to_string(),
+    }
+}
+
+fn format_record(record: &JournalRecord) -> String {
+    let location = match &record.xdg_directory {
+        Some(xdg_dir) => format!("[{}] {}", xdg_dir, record.address),
+        None => record.address.clone(),
+    };
+
+    format!(
+        "{} {} {}: {} -> {}",
+        record.timestamp,
+        record.command,
+        location,
+        format_value(&record.old_value),
+        format_value(&record.new_value)
+    )
+}
+
+impl Command for LogCommand {
+    type Err = Error;
+
+    fn execute(&self) -> Result<(), Self::Err> {
+        let component_pattern = self
+            .component
+            .as_deref()
+            .map(Pattern::new)
+            .transpose()
+            .map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Invalid component pattern: {}", e),
+                )
+            })?;
+        let since = self.since.as_deref().map(parse_since).transpose()?;
+
+        for record in read_journal()? {
+            if let Some(pattern) = &component_pattern {
+                match &record.component {
+                    Some(component) if pattern.matches(component) => {}
+                    _ => continue,
+                }
+            }
+
+            if let Some(since) = since {
+                let timestamp = humantime::parse_rfc3339_weak(&record.timestamp)
+                    .unwrap_or(SystemTime::UNIX_EPOCH);
+                // Records only have second precision.
+                if timestamp + Duration::from_secs(1) <= since {
+                    continue;
+                }
+            }
+
+            println!("{}", format_record(&record));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,263 @@
+use crate::{
+    commands::Command,
+    config::{
+        get_cosmic_configurations, list_configuration_entries, read_raw_configuration,
+        validate_component_name, validate_entry_name, write_raw_configuration,
+    },
+    formats::FileFormat,
+    lock::lock_configuration,
+    utils::{skip_reason, SkipSummary},
+};
+use clap::Args;
+use serde::Deserialize;
+use std::{
+    fs,
+    io::{Error, ErrorKind},
+    path::PathBuf,
+};
+
+#[derive(Args)]
+pub struct MigrateCommand {
+    /// The component to migrate (e.g., 'com.system76.CosmicComp').
+    #[arg(short, long)]
+    pub component: String,
+    /// The configuration version to copy the entries from.
+    #[arg(long)]
+    pub from: u64,
+    /// The configuration version to copy the entries to.
+    #[arg(long)]
+    pub to: u64,
+    /// The XDG directory to use (e.g., 'config', 'cache', 'data').
+    #[arg(short, long, default_value = "config")]
+    pub xdg_dir: String,
+    /// File with rename, drop and wrap rules to apply while copying (supports JSON, TOML, RON).
+    #[arg(short, long)]
+    pub rules: Option<PathBuf>,
+    /// Replace entries that already have a different value in the new version.
+    #[arg(long)]
+    pub overwrite: bool,
+    /// Show what would be migrated without writing anything.
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Overwrite read-only entries.
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(Deserialize)]
+struct MigrationRules {
+    rules: Vec<MigrationRule>,
+}
+
+/// A change applied to entries while they are copied, in the order the rules are listed.
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum MigrationRule {
+    /// Copies the entry under another name.
+    Rename { from: String, to: String },
+    /// Leaves the entry behind.
+    Drop { entry: String },
+    /// Replaces `{}` in the template with the value (e.g., 'Some({})').
+    Wrap { entry: String, template: String },
+}
+
+/// What happens to an entry once the rules have been applied.
+enum Migration {
+    Copy { entry: String, value: Vec<u8> },
+    Drop,
+}
+
+impl MigrateCommand {
+    fn load_rules(&self) -> Result<Vec<MigrationRule>, Error> {
+        let Some(rules_path) = &self.rules else {
+            return Ok(Vec::new());
+        };
+
+        let rules: MigrationRules =
+            FileFormat::from_path(rules_path)?.deserialize(&fs::read_to_string(rules_path)?)?;
+
+        for rule in &rules.rules {
+            match rule {
+                MigrationRule::Rename { from, to } => {
+                    validate_entry_name(to).map_err(|e| {
+                        Error::new(
+                            e.kind(),
+                            format!(
+                                "The rename rule for '{}' has an invalid target: {}",
+                                from, e
+                            ),
+                        )
+                    })?;
+                }
+                MigrationRule::Wrap { entry, template } if !template.contains("{}") => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "The wrap template for '{}' must contain '{{}}' where the value goes",
+                            entry
+                        ),
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(rules.rules)
+    }
+
+    fn migrate_entry(
+        rules: &[MigrationRule],
+        mut entry: String,
+        mut value: Vec<u8>,
+    ) -> Result<Migration, Error> {
+        for rule in rules {
+            match rule {
+                MigrationRule::Drop { entry: dropped } if *dropped == entry => {
+                    return Ok(Migration::Drop)
+                }
+                MigrationRule::Rename { from, to } if *from == entry => entry = to.clone(),
+                MigrationRule::Wrap {
+                    entry: wrapped,
+                    template,
+                } if *wrapped == entry => {
+                    let inner = String::from_utf8(value).map_err(|_| {
+                        Error::new(
+                            ErrorKind::InvalidData,
+                            format!("Cannot wrap '{}', its value is not valid UTF-8", entry),
+                        )
+                    })?;
+                    value = template.replacen("{}", &inner, 1).into_bytes();
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Migration::Copy { entry, value })
+    }
+}
+
+impl Command for MigrateCommand {
+    type Err = Error;
+
+    fn execute(&self) -> Result<(), Self::Err> {
+        validate_component_name(&self.component)?;
+        if self.from == self.to {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "The source and target versions must differ",
+            ));
+        }
+
+        let rules = self.load_rules()?;
+        let _lock = lock_configuration()?;
+
+        let entries = list_configuration_entries(&get_cosmic_configurations(&self.xdg_dir)?)
+            .remove(&self.component)
+            .and_then(|mut versions| versions.remove(&self.from))
+            .unwrap_or_default();
+        if entries.is_empty() {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!(
+                    "No entries found for {}/v{} in {}",
+                    self.component, self.from, self.xdg_dir
+                ),
+            ));
+        }
+
+        let action = if self.dry_run {
+            "Would copy"
+        } else {
+            "Copying"
+        };
+        let mut migrated_count = 0;
+        let mut conflicts = Vec::new();
+        let mut skipped = SkipSummary::default();
+
+        for entry in entries {
+            let migration =
+                read_raw_configuration(&self.component, &self.from, &entry, &self.xdg_dir)
+                    .and_then(|value| Self::migrate_entry(&rules, entry.clone(), value));
+
+            let (target_entry, value) = match migration {
+                Ok(Migration::Drop) => {
+                    println!("Dropping {}/v{}/{}", self.component, self.from, entry);
+                    skipped.add("dropped by rules");
+                    continue;
+                }
+                Ok(Migration::Copy { entry, value }) => (entry, value),
+                Err(e) => {
+                    eprintln!(
+                        "Error migrating {}/v{}/{}: {}",
+                        self.component, self.from, entry, e
+                    );
+                    skipped.add(skip_reason(&e));
+                    continue;
+                }
+            };
+
+            match read_raw_configuration(&self.component, &self.to, &target_entry, &self.xdg_dir) {
+                Ok(existing) if existing == value => {
+                    skipped.add("unchanged");
+                    continue;
+                }
+                Ok(_) if !self.overwrite => {
+                    conflicts.push(target_entry);
+                    skipped.add("already in the new version");
+                    continue;
+                }
+                _ => {}
+            }
+
+            println!(
+                "{} {}/v{}/{} to v{}/{}",
+                action, self.component, self.from, entry, self.to, target_entry
+            );
+            if !self.dry_run {
+                if let Err(e) = write_raw_configuration(
+                    &self.component,
+                    &self.to,
+                    &target_entry,
+                    &value,
+                    &self.xdg_dir,
+                    self.force,
+                ) {
+                    eprintln!(
+                        "Error writing {}/v{}/{}: {}",
+                        self.component, self.to, target_entry, e
+                    );
+                    skipped.add(skip_reason(&e));
+                    continue;
+                }
+            }
+            migrated_count += 1;
+        }
+
+        if !conflicts.is_empty() {
+            println!(
+                "{} entries exist in both versions with different values, use --overwrite to replace them:",
+                conflicts.len()
+            );
+            for conflict in &conflicts {
+                println!("  {}/v{}/{}", self.component, self.to, conflict);
+            }
+        }
+
+        if self.dry_run {
+            println!(
+                "Dry run: {} entries would be migrated from v{} to v{}.",
+                migrated_count, self.from, self.to
+            );
+        } else {
+            println!(
+                "Migrated {} entries from v{} to v{}.",
+                migrated_count, self.from, self.to
+            );
+        }
+        if let Some(skipped) = skipped.describe() {
+            println!("{}", skipped);
+        }
+
+        Ok(())
+    }
+}
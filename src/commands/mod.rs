@@ -1,13 +1,27 @@
 pub mod apply;
 pub mod backup;
+pub mod convert;
+pub mod cp;
 pub mod delete;
+pub mod describe;
+pub mod edit;
+pub mod export;
+pub mod import;
+pub mod log;
+pub mod migrate;
+pub mod mv;
 pub mod read;
 pub mod reset;
+pub mod search;
+pub mod tui;
 pub mod write;
 
 use crate::commands::{
-    apply::ApplyCommand, backup::BackupCommand, delete::DeleteCommand, read::ReadCommand,
-    reset::ResetCommand, write::WriteCommand,
+    apply::ApplyCommand, backup::BackupCommand, convert::ConvertCommand, cp::CpCommand,
+    delete::DeleteCommand, describe::DescribeCommand, edit::EditCommand, export::ExportCommand,
+    import::ImportCommand, log::LogCommand, migrate::MigrateCommand, mv::MvCommand,
+    read::ReadCommand, reset::ResetCommand, search::SearchCommand, tui::TuiCommand,
+    write::WriteCommand,
 };
 use clap::Subcommand;
 use std::io::Error;
@@ -18,27 +32,89 @@ pub enum Commands {
     Apply(ApplyCommand),
     /// Backup all configuration entries to a JSON file.
     Backup(BackupCommand),
+    /// Convert an apply file between JSON, TOML and RON.
+    Convert(ConvertCommand),
+    /// Copy configuration entries to another component, version or XDG directory.
+    #[command(disable_version_flag = true)]
+    Cp(CpCommand),
     /// Delete a configuration entry.
     #[command(disable_version_flag = true)]
     Delete(DeleteCommand),
+    /// Show the known entries of a component, their types and defaults.
+    #[command(disable_version_flag = true)]
+    Describe(DescribeCommand),
+    /// Edit a configuration entry in your editor.
+    #[command(disable_version_flag = true)]
+    Edit(EditCommand),
+    /// Export configuration entries as a directory tree of files.
+    Export(ExportCommand),
+    /// Import configuration entries from a directory tree written by export.
+    Import(ImportCommand),
+    /// Show the history of changes made by cosmic-ctl.
+    Log(LogCommand),
+    /// Copy a component's entries from one configuration version to another.
+    #[command(disable_version_flag = true)]
+    Migrate(MigrateCommand),
+    /// Move configuration entries to another component, version or XDG directory.
+    #[command(disable_version_flag = true)]
+    Mv(MvCommand),
     /// Read a configuration entry.
     #[command(disable_version_flag = true)]
     Read(ReadCommand),
     /// Delete all configuration entries.
+    #[command(disable_version_flag = true)]
     Reset(ResetCommand),
+    /// Search configuration entries by address and value.
+    Search(SearchCommand),
+    /// Browse and edit configuration entries in a full-screen interface.
+    Tui(TuiCommand),
     /// Write a configuration entry.
     #[command(disable_version_flag = true)]
     Write(WriteCommand),
 }
 
 impl Commands {
+    /// Name of the command, as recorded in the journal.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Commands::Apply(_) => "apply",
+            Commands::Backup(_) => "backup",
+            Commands::Convert(_) => "convert",
+            Commands::Cp(_) => "cp",
+            Commands::Delete(_) => "delete",
+            Commands::Describe(_) => "describe",
+            Commands::Edit(_) => "edit",
+            Commands::Export(_) => "export",
+            Commands::Import(_) => "import",
+            Commands::Log(_) => "log",
+            Commands::Migrate(_) => "migrate",
+            Commands::Mv(_) => "mv",
+            Commands::Read(_) => "read",
+            Commands::Reset(_) => "reset",
+            Commands::Search(_) => "search",
+            Commands::Tui(_) => "tui",
+            Commands::Write(_) => "write",
+        }
+    }
+
     pub(crate) fn execute(&self) -> Result<(), Error> {
         match self {
             Commands::Apply(cmd) => cmd.execute(),
             Commands::Backup(cmd) => cmd.execute(),
+            Commands::Convert(cmd) => cmd.execute(),
+            Commands::Cp(cmd) => cmd.execute(),
             Commands::Delete(cmd) => cmd.execute(),
+            Commands::Describe(cmd) => cmd.execute(),
+            Commands::Edit(cmd) => cmd.execute(),
+            Commands::Export(cmd) => cmd.execute(),
+            Commands::Import(cmd) => cmd.execute(),
+            Commands::Log(cmd) => cmd.execute(),
+            Commands::Migrate(cmd) => cmd.execute(),
+            Commands::Mv(cmd) => cmd.execute(),
             Commands::Read(cmd) => cmd.execute(),
             Commands::Reset(cmd) => cmd.execute(),
+            Commands::Search(cmd) => cmd.execute(),
+            Commands::Tui(cmd) => cmd.execute(),
             Commands::Write(cmd) => cmd.execute(),
         }
     }
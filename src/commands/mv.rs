@@ -0,0 +1,17 @@
+use crate::commands::{cp::CpCommand, Command};
+use clap::Args;
+use std::io::Error;
+
+#[derive(Args)]
+pub struct MvCommand {
+    #[command(flatten)]
+    pub args: CpCommand,
+}
+
+impl Command for MvCommand {
+    type Err = Error;
+
+    fn execute(&self) -> Result<(), Self::Err> {
+        self.args.transfer(true)
+    }
+}
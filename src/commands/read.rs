@@ -1,15 +1,18 @@
 use crate::{
     commands::Command,
-    config::{read_configuration, read_configuration_file},
+    config::{read_raw_configuration, read_raw_configuration_file, resolve_latest_version},
 };
 use clap::Args;
-use std::{io::Error, path::PathBuf};
+use std::{
+    io::{stdout, Error, ErrorKind, Write},
+    path::PathBuf,
+};
 
 #[derive(Args)]
 pub struct ReadCommand {
-    /// The configuration version of the component.
-    #[arg(short, long, default_value_t = 1)]
-    pub version: u64,
+    /// The configuration version of the component (defaults to the latest one present).
+    #[arg(short, long)]
+    pub version: Option<u64>,
     /// The component to configure (e.g., 'com.system76.CosmicComp').
     #[arg(short, long, required_unless_present = "file")]
     pub component: Option<String>,
@@ -22,6 +25,28 @@ pub struct ReadCommand {
     /// Direct path to the configuration file.
     #[arg(short, long, required_unless_present_all = &["component", "entry"])]
     pub file: Option<PathBuf>,
+    /// Write the value's bytes to stdout as-is, even if it is not valid UTF-8.
+    #[arg(long)]
+    pub raw: bool,
+}
+
+impl ReadCommand {
+    fn print_value(&self, bytes: Vec<u8>) -> Result<(), Error> {
+        if self.raw {
+            let mut stdout = stdout();
+            stdout.write_all(&bytes)?;
+            return stdout.flush();
+        }
+
+        let value = String::from_utf8(bytes).map_err(|_| {
+            Error::new(
+                ErrorKind::InvalidData,
+                "Value is not valid UTF-8, use --raw to read its bytes",
+            )
+        })?;
+        println!("{}", value);
+        Ok(())
+    }
 }
 
 impl Command for ReadCommand {
@@ -29,27 +54,29 @@ impl Command for ReadCommand {
 
     fn execute(&self) -> Result<(), Self::Err> {
         if let Some(file_path) = &self.file {
-            match read_configuration_file(file_path) {
-                Ok(value) => {
-                    println!("{}", value);
-                    Ok(())
-                }
+            match read_raw_configuration_file(file_path).and_then(|bytes| self.print_value(bytes)) {
+                Ok(()) => Ok(()),
                 Err(e) => {
                     eprintln!("Error reading configuration file: {}", e);
                     Err(e)
                 }
             }
         } else {
-            match read_configuration(
-                self.component.as_ref().unwrap(),
-                &self.version,
+            let component = self.component.as_ref().unwrap();
+            let version = match self.version {
+                Some(version) => version,
+                None => resolve_latest_version(component, &self.xdg_dir)?,
+            };
+
+            match read_raw_configuration(
+                component,
+                &version,
                 self.entry.as_ref().unwrap(),
                 &self.xdg_dir,
-            ) {
-                Ok(value) => {
-                    println!("{}", value);
-                    Ok(())
-                }
+            )
+            .and_then(|bytes| self.print_value(bytes))
+            {
+                Ok(()) => Ok(()),
                 Err(e) => {
                     eprintln!("Error reading configuration entry: {}", e);
                     Err(e)
@@ -1,13 +1,15 @@
 use crate::{
     commands::Command,
-    config::{delete_configuration, get_cosmic_configurations, parse_configuration_path},
-    utils::split_string_respect_braces,
+    config::delete_configuration,
+    lock::lock_configuration,
+    scan::{scan_xdg_dirs, ScannedEntry},
+    utils::PathFilter,
 };
-use bracoxide::explode;
 use clap::Args;
-use glob::Pattern;
-use std::io::{stdin, stdout, Error, Write};
-use walkdir::WalkDir;
+use std::{
+    collections::HashSet,
+    io::{stdin, stdout, Error, ErrorKind, Write},
+};
 
 #[derive(Args)]
 pub struct ResetCommand {
@@ -17,100 +19,180 @@ pub struct ResetCommand {
     /// Show which entries are being deleted.
     #[arg(short, long)]
     pub verbose: bool,
+    /// Patterns to limit the reset to (comma-separated).
+    #[arg(long)]
+    pub only: Option<String>,
     /// Patterns to exclude from reset (comma-separated).
     #[arg(long)]
     pub exclude: Option<String>,
+    /// Only reset entries of this component (e.g., 'com.system76.CosmicComp').
+    #[arg(short, long, conflicts_with = "only")]
+    pub component: Option<String>,
+    /// Only reset this configuration version of the component.
+    #[arg(long, requires = "component")]
+    pub version: Option<u64>,
     /// The XDG directories to backup (comma-separated) (e.g., 'config,cache,data').
     #[arg(short, long, value_delimiter = ',', default_value = "config,state")]
     pub xdg_dirs: Vec<String>,
 }
 
+struct ResetTarget {
+    entry: ScannedEntry,
+    excluded: bool,
+}
+
+impl ResetCommand {
+    fn include_patterns(&self) -> Option<String> {
+        match (&self.component, self.version) {
+            (Some(component), Some(version)) => Some(format!("{}/v{}", component, version)),
+            (Some(component), None) => Some(component.clone()),
+            (None, _) => self.only.clone(),
+        }
+    }
+
+    /// The entries that would be deleted, by XDG directory and path.
+    fn selected(&self, scanned: &[Option<Vec<ResetTarget>>]) -> HashSet<(String, String)> {
+        self.xdg_dirs
+            .iter()
+            .zip(scanned)
+            .flat_map(|(xdg_dir, targets)| {
+                targets
+                    .iter()
+                    .flatten()
+                    .filter(|target| !target.excluded)
+                    .map(move |target| (xdg_dir.clone(), target.entry.relative_path.clone()))
+            })
+            .collect()
+    }
+
+    /// Collects the entries of each XDG directory, or `None` if the directory does not exist.
+    fn scan(&self, filter: &PathFilter) -> Result<Vec<Option<Vec<ResetTarget>>>, Error> {
+        let scanned = scan_xdg_dirs(&self.xdg_dirs, false)?;
+
+        Ok(scanned
+            .into_iter()
+            .map(|scanned| {
+                // Entries inside symlinked directories belong to the link target.
+                for symlinked_dir in &scanned.symlinked_dirs {
+                    println!(
+                        "Skipping symlinked directory [{}]: {}",
+                        scanned.xdg_dir,
+                        symlinked_dir.display()
+                    );
+                }
+
+                scanned.entries.map(|entries| {
+                    entries
+                        .into_iter()
+                        .map(|entry| ResetTarget {
+                            excluded: filter.is_excluded(&entry.relative_path),
+                            entry,
+                        })
+                        .collect()
+                })
+            })
+            .collect())
+    }
+}
+
 impl Command for ResetCommand {
     type Err = Error;
 
     fn execute(&self) -> Result<(), Self::Err> {
-        if !self.force {
-            print!("Are you sure you want to delete all configuration entries from XDG directories {}? This action cannot be undone. [y/N] ",
-                self.xdg_dirs.join(", "));
-            stdout().flush()?;
+        let filter = PathFilter::new(self.include_patterns(), self.exclude.clone())?;
 
-            let mut response = String::new();
-            stdin().read_line(&mut response)?;
+        let mut confirmed = None;
+        if !self.force {
+            let scanned = self.scan(&filter)?;
+            confirmed = Some(self.selected(&scanned));
+            let targets = scanned
+                .iter()
+                .flatten()
+                .flatten()
+                .filter(|target| !target.excluded);
+            let entry_count = targets.clone().count();
+            let component_count = targets
+                .map(|target| target.entry.component.as_str())
+                .collect::<HashSet<_>>()
+                .len();
+
+            if entry_count > 0 {
+                print!("Are you sure you want to delete {} configuration entries from {} components in XDG directories {}? This action cannot be undone. [y/N] ",
+                    entry_count, component_count, self.xdg_dirs.join(", "));
+                stdout().flush()?;
+
+                let mut response = String::new();
+                stdin().read_line(&mut response)?;
+
+                if !response.trim().eq_ignore_ascii_case("y") {
+                    println!("Operation cancelled.");
+                    return Ok(());
+                }
+            }
+        }
 
-            if !response.trim().eq_ignore_ascii_case("y") {
-                println!("Operation cancelled.");
-                return Ok(());
+        // Other processes may run while the prompt is open, so scan again under the lock.
+        let _lock = lock_configuration()?;
+        let scanned = self.scan(&filter)?;
+        if let Some(confirmed) = confirmed {
+            let added = self.selected(&scanned).difference(&confirmed).count();
+            if added > 0 {
+                return Err(Error::new(
+                    ErrorKind::Interrupted,
+                    format!(
+                        "{} configuration entries were added while waiting for confirmation, nothing was deleted. Run reset again to review them.",
+                        added
+                    ),
+                ));
             }
         }
 
         let mut total_deleted_count = 0;
         let mut all_errors = Vec::new();
 
-        for xdg_dir in &self.xdg_dirs {
-            let cosmic_path = get_cosmic_configurations(xdg_dir)?;
+        for (xdg_dir, targets) in self.xdg_dirs.iter().zip(scanned) {
             let mut deleted_count = 0;
             let mut errors = Vec::new();
 
-            if !cosmic_path.exists() {
+            let Some(targets) = targets else {
                 if self.verbose {
                     println!("No configuration entries found in {}.", xdg_dir);
                 }
-                errors.push(format!("No configuration entries found in {}.", xdg_dir));
                 continue;
-            }
-
-            let exclude_patterns = split_string_respect_braces(self.exclude.clone())
-                .into_iter()
-                .flat_map(|pattern| explode(&pattern).unwrap_or_else(|_| vec![pattern.clone()]))
-                .filter_map(|pattern| {
-                    let pattern = if !pattern.contains('/') {
-                        format!("{}/**", pattern)
-                    } else if pattern.matches('/').count() == 1 {
-                        format!("{}/*", pattern)
-                    } else {
-                        pattern
-                    };
-
-                    match Pattern::new(&pattern) {
-                        Ok(p) => Some(p),
-                        Err(e) => {
-                            errors.push(format!("Invalid exclude pattern '{}': {}", pattern, e));
-                            None
-                        }
-                    }
-                })
-                .collect::<Vec<_>>();
-
-            for entry in WalkDir::new(&cosmic_path)
-                .into_iter()
-                .filter_map(|e| e.ok())
-                .filter(|e| e.path().is_file())
-            {
-                if let Some((component, version, entry_name)) =
-                    parse_configuration_path(entry.path())
-                {
-                    let relative_path = format!("{}/v{}/{}", component, version, entry_name);
-                    let should_exclude = exclude_patterns
-                        .iter()
-                        .any(|pattern| pattern.matches(&relative_path));
-
-                    if should_exclude {
-                        if self.verbose {
-                            println!("Skipping excluded path [{}]: {}", xdg_dir, relative_path);
-                        }
-                        continue;
-                    }
+            };
 
+            for ResetTarget { entry, excluded } in targets {
+                if excluded {
                     if self.verbose {
-                        println!("Deleting [{}]: {}", xdg_dir, entry.path().display());
+                        println!(
+                            "Skipping excluded path [{}]: {}",
+                            xdg_dir, entry.relative_path
+                        );
                     }
+                    continue;
+                }
 
-                    match delete_configuration(&component, &version, &entry_name, xdg_dir) {
-                        Ok(()) => deleted_count += 1,
-                        Err(e) => {
-                            errors.push(format!("[{}] {}: {}", xdg_dir, entry.path().display(), e))
-                        }
+                match &entry.symlink_target {
+                    Some(link_target) => println!(
+                        "Removing symlink [{}]: {} (keeping {})",
+                        xdg_dir,
+                        entry.path.display(),
+                        link_target.display()
+                    ),
+                    None if self.verbose => {
+                        println!("Deleting [{}]: {}", xdg_dir, entry.path.display())
                     }
+                    None => {}
+                }
+
+                match delete_configuration(
+                    &entry.component,
+                    &entry.version,
+                    &entry.entry_name,
+                    xdg_dir,
+                ) {
+                    Ok(()) => deleted_count += 1,
+                    Err(e) => errors.push(format!("[{}] {}: {}", xdg_dir, entry.path.display(), e)),
                 }
             }
 
@@ -0,0 +1,206 @@
+use crate::{
+    commands::Command,
+    config::get_system_default_dirs,
+    scan::{read_entries, scan_directory, scan_xdg_dirs, ScannedDirectory, ScannedEntry},
+};
+use clap::Args;
+use glob::Pattern;
+use regex::Regex;
+use serde::Serialize;
+use std::io::{stdout, Error, ErrorKind, IsTerminal};
+
+const HIGHLIGHT_START: &str = "\x1b[1;31m";
+const HIGHLIGHT_END: &str = "\x1b[0m";
+
+#[derive(Args)]
+pub struct SearchCommand {
+    /// Regular expression to search for (e.g., 'wallpaper|background').
+    pub pattern: String,
+    /// Only match against component/version/entry addresses.
+    #[arg(long, conflicts_with = "values_only")]
+    pub keys_only: bool,
+    /// Only match against entry values.
+    #[arg(long)]
+    pub values_only: bool,
+    /// The XDG directories to search (comma-separated) (e.g., 'config,cache,data').
+    #[arg(short, long, value_delimiter = ',', default_value = "config,state")]
+    pub xdg_dirs: Vec<String>,
+    /// Only search components matching this pattern (e.g., 'com.system76.CosmicPanel*').
+    #[arg(short, long)]
+    pub component: Option<String>,
+    /// Also search the system defaults.
+    #[arg(long)]
+    pub defaults: bool,
+    /// Output format ('text' or 'json').
+    #[arg(short, long, default_value = "text")]
+    pub output: String,
+}
+
+/// A line of an entry value matching the pattern.
+#[derive(Serialize)]
+struct LineMatch {
+    line: usize,
+    text: String,
+}
+
+#[derive(Serialize)]
+struct SearchMatch {
+    /// The XDG directory, or `defaults` for the system defaults.
+    source: String,
+    address: String,
+    key_match: bool,
+    lines: Vec<LineMatch>,
+}
+
+impl SearchCommand {
+    fn search_directory(
+        &self,
+        source: &str,
+        scanned: ScannedDirectory,
+        regex: &Regex,
+        component_pattern: Option<&Pattern>,
+        matches: &mut Vec<SearchMatch>,
+    ) {
+        let entries: Vec<ScannedEntry> = scanned
+            .entries
+            .into_iter()
+            .flatten()
+            .filter(|entry| {
+                component_pattern.is_none_or(|pattern| pattern.matches(&entry.component))
+            })
+            .collect();
+
+        let values = if self.keys_only {
+            Vec::new()
+        } else {
+            read_entries(&entries)
+        };
+
+        for (index, entry) in entries.iter().enumerate() {
+            let key_match = !self.values_only && regex.is_match(&entry.relative_path);
+
+            let lines = match values.get(index) {
+                Some((_, Ok(bytes))) => String::from_utf8_lossy(bytes)
+                    .lines()
+                    .enumerate()
+                    .filter(|(_, text)| regex.is_match(text))
+                    .map(|(index, text)| LineMatch {
+                        line: index + 1,
+                        text: text.to_string(),
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            };
+
+            if key_match || !lines.is_empty() {
+                matches.push(SearchMatch {
+                    source: source.to_string(),
+                    address: entry.relative_path.clone(),
+                    key_match,
+                    lines,
+                });
+            }
+        }
+    }
+}
+
+impl Command for SearchCommand {
+    type Err = Error;
+
+    fn execute(&self) -> Result<(), Self::Err> {
+        let regex = Regex::new(&self.pattern).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("Invalid search pattern: {}", e),
+            )
+        })?;
+        let component_pattern = self
+            .component
+            .as_deref()
+            .map(Pattern::new)
+            .transpose()
+            .map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Invalid component pattern: {}", e),
+                )
+            })?;
+
+        let mut matches = Vec::new();
+        for scanned in scan_xdg_dirs(&self.xdg_dirs, false)? {
+            let xdg_dir = scanned.xdg_dir.clone();
+            self.search_directory(
+                &xdg_dir,
+                scanned,
+                &regex,
+                component_pattern.as_ref(),
+                &mut matches,
+            );
+        }
+
+        if self.defaults {
+            for default_dir in get_system_default_dirs() {
+                self.search_directory(
+                    "defaults",
+                    scan_directory("defaults", &default_dir, false),
+                    &regex,
+                    component_pattern.as_ref(),
+                    &mut matches,
+                );
+            }
+        }
+
+        match self.output.to_lowercase().as_str() {
+            "json" => {
+                let json = serde_json::to_string_pretty(&matches).map_err(|e| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!("JSON serialization error: {}", e),
+                    )
+                })?;
+                println!("{}", json);
+            }
+            "text" => print_matches(&matches, &regex, stdout().is_terminal()),
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Unsupported output format: {}", self.output),
+                ))
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn highlight(text: &str, regex: &Regex, enabled: bool) -> String {
+    if enabled {
+        regex
+            .replace_all(text, |captures: &regex::Captures| {
+                format!("{}{}{}", HIGHLIGHT_START, &captures[0], HIGHLIGHT_END)
+            })
+            .to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+fn print_matches(matches: &[SearchMatch], regex: &Regex, colored: bool) {
+    if matches.is_empty() {
+        println!("No matches found.");
+        return;
+    }
+
+    for search_match in matches {
+        let address = if search_match.key_match {
+            highlight(&search_match.address, regex, colored)
+        } else {
+            search_match.address.clone()
+        };
+        println!("[{}] {}", search_match.source, address);
+
+        for line in &search_match.lines {
+            println!("  {}: {}", line.line, highlight(&line.text, regex, colored));
+        }
+    }
+}
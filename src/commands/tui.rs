@@ -0,0 +1,25 @@
+use crate::{commands::Command, tui::run_tui};
+use clap::Args;
+use std::io::{Error, ErrorKind};
+
+#[derive(Args)]
+pub struct TuiCommand {
+    /// The XDG directories to browse (comma-separated) (e.g., 'config,cache,data').
+    #[arg(short, long, value_delimiter = ',', default_value = "config,state")]
+    pub xdg_dirs: Vec<String>,
+}
+
+impl Command for TuiCommand {
+    type Err = Error;
+
+    fn execute(&self) -> Result<(), Self::Err> {
+        if self.xdg_dirs.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "At least one XDG directory must be selected",
+            ));
+        }
+
+        run_tui(self.xdg_dirs.clone(), 0)
+    }
+}
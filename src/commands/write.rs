@@ -1,15 +1,17 @@
 use crate::{
+    catalog::load_catalog,
     commands::Command,
-    config::{write_configuration, write_configuration_file},
+    config::{resolve_latest_version, write_configuration, write_configuration_file},
 };
 use clap::Args;
 use std::{io::Error, path::PathBuf};
+use unescaper::unescape;
 
 #[derive(Args)]
 pub struct WriteCommand {
-    /// The configuration version of the component.
-    #[arg(short, long, default_value_t = 1)]
-    pub version: u64,
+    /// The configuration version of the component (defaults to the latest one present).
+    #[arg(short, long)]
+    pub version: Option<u64>,
     /// The component to configure (e.g., 'com.system76.CosmicComp').
     #[arg(short, long, required_unless_present = "file")]
     pub component: Option<String>,
@@ -24,6 +26,12 @@ pub struct WriteCommand {
     /// Direct path to the configuration file.
     #[arg(long, required_unless_present_all = &["component", "entry"])]
     pub file: Option<PathBuf>,
+    /// Overwrite read-only entries.
+    #[arg(short, long)]
+    pub force: bool,
+    /// Warn if the entry is not in the catalog or the value doesn't match its type.
+    #[arg(long, conflicts_with = "file")]
+    pub check: bool,
 }
 
 impl Command for WriteCommand {
@@ -31,7 +39,7 @@ impl Command for WriteCommand {
 
     fn execute(&self) -> Result<(), Self::Err> {
         if let Some(file_path) = &self.file {
-            match write_configuration_file(file_path, &self.value) {
+            match write_configuration_file(file_path, &self.value, self.force) {
                 Ok(true) => {
                     println!("Configuration file written successfully.");
                     Ok(())
@@ -46,12 +54,31 @@ impl Command for WriteCommand {
                 }
             }
         } else {
+            let component = self.component.as_ref().unwrap();
+            let version = match self.version {
+                Some(version) => version,
+                None => resolve_latest_version(component, &self.xdg_dir)?,
+            };
+
+            if self.check {
+                let value = unescape(&self.value).unwrap_or_else(|_| self.value.clone());
+                if let Some(warning) = load_catalog()?.check_entry(
+                    component,
+                    version,
+                    self.entry.as_ref().unwrap(),
+                    Some(&value),
+                ) {
+                    eprintln!("Warning: {}", warning);
+                }
+            }
+
             match write_configuration(
-                self.component.as_ref().unwrap(),
-                &self.version,
+                component,
+                &version,
                 self.entry.as_ref().unwrap(),
                 &self.value,
                 &self.xdg_dir,
+                self.force,
             ) {
                 Ok(true) => {
                     println!("Configuration entry written successfully.");
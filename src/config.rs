@@ -1,14 +1,487 @@
+use crate::{
+    journal::{record_configuration_change, record_file_change},
+    lock::lock_configuration,
+};
 use atomicwrites::{AtomicFile, OverwriteBehavior};
 use etcetera::{
     base_strategy::{BaseStrategy, Xdg},
     choose_base_strategy,
 };
 use std::{
+    collections::{BTreeMap, BTreeSet},
+    env,
+    ffi::{CStr, CString, OsStr, OsString},
     fs,
     io::{Error, ErrorKind, Write},
-    path::{Path, PathBuf},
+    mem,
+    os::unix::{
+        ffi::OsStrExt,
+        fs::{chown, fchown, MetadataExt},
+    },
+    path::{Component, Path, PathBuf},
+    ptr,
+    sync::OnceLock,
 };
 use unescaper::unescape;
+use xattr::FileExt;
+
+/// Overrides for where paths are resolved. The global ones are set once from the CLI options.
+#[derive(Default)]
+pub struct PathOverrides {
+    /// Directory every resolved path is placed under (e.g., a mounted image).
+    pub root: Option<PathBuf>,
+    /// Home directory the XDG directories are resolved from.
+    pub home: Option<PathBuf>,
+    /// User and group id given to every file and directory created.
+    pub owner: Option<(u32, u32)>,
+    /// XDG base directories set in the user's environment, keyed by name (e.g., 'config').
+    pub xdg_dirs: BTreeMap<String, PathBuf>,
+    /// Replace symlinked entries with regular files instead of writing through to their targets.
+    pub replace_symlinks: bool,
+}
+
+/// An account from the passwd database.
+pub struct UserAccount {
+    pub uid: u32,
+    pub gid: u32,
+    pub home: PathBuf,
+}
+
+const MAX_SYMLINK_DEPTH: usize = 40;
+
+static PATH_OVERRIDES: OnceLock<PathOverrides> = OnceLock::new();
+
+impl PathOverrides {
+    /// The overrides for operating on a user's configuration: their home, XDG directories
+    /// and ownership of every file and directory created.
+    pub fn for_user(
+        name: &str,
+        root: Option<PathBuf>,
+        replace_symlinks: bool,
+    ) -> Result<Self, Error> {
+        let account = lookup_user(name, root.as_deref())?;
+
+        Ok(PathOverrides {
+            xdg_dirs: read_user_xdg_dirs(&account.home, root.as_deref()),
+            root,
+            home: Some(account.home),
+            owner: Some((account.uid, account.gid)),
+            replace_symlinks,
+        })
+    }
+
+    pub fn xdg_dir_path(&self, xdg_dir: &str) -> Result<PathBuf, Error> {
+        if let Some(home) = &self.home {
+            if let Some(path) = self.xdg_dirs.get(&xdg_dir.to_lowercase()) {
+                return Ok(under_root(path.clone()));
+            }
+
+            let path = match xdg_dir.to_lowercase().as_str() {
+                "config" => home.join(".config"),
+                "data" => home.join(".local").join("share"),
+                "cache" => home.join(".cache"),
+                "state" => home.join(".local").join("state"),
+                "runtime" => {
+                    return Err(Error::new(
+                        ErrorKind::NotFound,
+                        "Runtime directory is not available for an overridden home directory",
+                    ))
+                }
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("Invalid XDG directory: {}", xdg_dir),
+                    ))
+                }
+            };
+
+            return Ok(under_root(path));
+        }
+
+        Ok(under_root(get_own_xdg_dir_path(xdg_dir)?))
+    }
+}
+
+/// An XDG directory of the user running cosmic-ctl, ignoring `--root`, `--home` and `--user`.
+pub fn get_own_xdg_dir_path(xdg_dir: &str) -> Result<PathBuf, Error> {
+    match xdg_dir.to_lowercase().as_str() {
+        "config" => Ok(get_base_strategy()?.config_dir()),
+        "data" => Ok(get_base_strategy()?.data_dir()),
+        "cache" => Ok(get_base_strategy()?.cache_dir()),
+        "state" => get_base_strategy()?
+            .state_dir()
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, "State directory is not available")),
+        "runtime" => get_base_strategy()?
+            .runtime_dir()
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, "Runtime directory is not available")),
+        _ => Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("Invalid XDG directory: {}", xdg_dir),
+        )),
+    }
+}
+
+pub fn set_path_overrides(overrides: PathOverrides) {
+    let _ = PATH_OVERRIDES.set(overrides);
+}
+
+fn get_path_overrides() -> &'static PathOverrides {
+    PATH_OVERRIDES.get_or_init(PathOverrides::default)
+}
+
+/// The overrides for another user's configuration, inside the same root directory.
+pub fn user_path_overrides(name: &str) -> Result<PathOverrides, Error> {
+    let overrides = get_path_overrides();
+    PathOverrides::for_user(name, overrides.root.clone(), overrides.replace_symlinks)
+}
+
+/// Places an absolute path under the root directory, if one is set.
+fn under_root(path: PathBuf) -> PathBuf {
+    match &get_path_overrides().root {
+        Some(root) => root.join(path.strip_prefix("/").unwrap_or(&path)),
+        None => path,
+    }
+}
+
+/// Follows a chain of symlinks to the file it finally points to. With a root directory set,
+/// absolute targets are resolved under it and targets outside of it are rejected.
+pub fn resolve_symlink(path: &Path) -> Result<PathBuf, Error> {
+    let mut resolved = path.to_path_buf();
+
+    for _ in 0..MAX_SYMLINK_DEPTH {
+        if !fs::symlink_metadata(&resolved).is_ok_and(|metadata| metadata.is_symlink()) {
+            return Ok(resolved);
+        }
+
+        let target = fs::read_link(&resolved)?;
+        resolved = match resolved.parent() {
+            _ if target.is_absolute() => under_root(target),
+            Some(parent) => parent.join(target),
+            None => target,
+        };
+
+        if let Some(root) = &get_path_overrides().root {
+            resolved = remove_dot_components(&resolved);
+            if !resolved.starts_with(remove_dot_components(root)) {
+                return Err(Error::new(
+                    ErrorKind::PermissionDenied,
+                    format!(
+                        "Symlink {} points outside the root directory {}",
+                        path.display(),
+                        root.display()
+                    ),
+                ));
+            }
+        }
+    }
+
+    Err(Error::new(
+        ErrorKind::InvalidInput,
+        format!("Too many levels of symbolic links: {}", path.display()),
+    ))
+}
+
+/// Reads a file through its symlinks, resolved like [`resolve_symlink`] so that a root
+/// directory confines them.
+pub fn read_resolved(path: &Path) -> Result<Vec<u8>, Error> {
+    fs::read(resolve_symlink(path)?)
+}
+
+/// Resolves `.` and `..` without touching the filesystem, so no symlink is followed.
+fn remove_dot_components(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::CurDir => {}
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Looks up a user through the system's user database (NSS), or in the passwd file
+/// inside the root directory if one is given.
+fn lookup_user(name: &str, root: Option<&Path>) -> Result<UserAccount, Error> {
+    let account = match root {
+        Some(root) => lookup_passwd_file(name, &root.join("etc/passwd"))?,
+        None => lookup_system_user(name)?,
+    };
+
+    account.ok_or_else(|| Error::new(ErrorKind::NotFound, format!("Unknown user: {}", name)))
+}
+
+fn lookup_passwd_file(name: &str, passwd_path: &Path) -> Result<Option<UserAccount>, Error> {
+    let passwd = fs::read_to_string(passwd_path).map_err(|e| {
+        Error::new(
+            e.kind(),
+            format!("Failed to read {}: {}", passwd_path.display(), e),
+        )
+    })?;
+
+    Ok(passwd
+        .lines()
+        .map(|line| line.split(':').collect::<Vec<_>>())
+        .find(|fields| fields.len() >= 7 && fields[0] == name)
+        .and_then(|fields| {
+            Some(UserAccount {
+                uid: fields[2].parse().ok()?,
+                gid: fields[3].parse().ok()?,
+                home: PathBuf::from(fields[5]),
+            })
+        }))
+}
+
+/// Asks getpwnam_r, so LDAP, SSSD and systemd-homed accounts are found too.
+fn lookup_system_user(name: &str) -> Result<Option<UserAccount>, Error> {
+    let c_name = CString::new(name)
+        .map_err(|_| Error::new(ErrorKind::InvalidInput, format!("Invalid user: {}", name)))?;
+    let mut buffer: Vec<libc::c_char> = vec![0; 4096];
+
+    loop {
+        // SAFETY: `passwd` only holds integers and pointers, for which zero is valid.
+        let mut passwd: libc::passwd = unsafe { mem::zeroed() };
+        let mut result: *mut libc::passwd = ptr::null_mut();
+        // SAFETY: every pointer is valid for the call and `buffer.len()` is its real size.
+        let code = unsafe {
+            libc::getpwnam_r(
+                c_name.as_ptr(),
+                &mut passwd,
+                buffer.as_mut_ptr(),
+                buffer.len(),
+                &mut result,
+            )
+        };
+
+        match code {
+            libc::ERANGE => buffer.resize(buffer.len() * 2, 0),
+            0 if result.is_null() => return Ok(None),
+            0 => {
+                // SAFETY: on success, pw_dir is a NUL-terminated string inside `buffer`.
+                let home = unsafe { CStr::from_ptr(passwd.pw_dir) };
+                return Ok(Some(UserAccount {
+                    uid: passwd.pw_uid,
+                    gid: passwd.pw_gid,
+                    home: PathBuf::from(OsStr::from_bytes(home.to_bytes())),
+                }));
+            }
+            code => {
+                let e = Error::from_raw_os_error(code);
+                return Err(Error::new(
+                    e.kind(),
+                    format!("Failed to look up user {}: {}", name, e),
+                ));
+            }
+        }
+    }
+}
+
+/// The XDG base directories a user sets in `~/.config/environment.d`, the environment
+/// their session starts with. `$HOME` and variables set earlier in the files are expanded.
+fn read_user_xdg_dirs(home: &Path, root: Option<&Path>) -> BTreeMap<String, PathBuf> {
+    let environment_dir = home.join(".config/environment.d");
+    let environment_dir = match root {
+        Some(root) => root.join(
+            environment_dir
+                .strip_prefix("/")
+                .unwrap_or(&environment_dir),
+        ),
+        None => environment_dir,
+    };
+
+    let mut files: Vec<PathBuf> = fs::read_dir(environment_dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "conf")
+        })
+        .collect();
+    files.sort();
+
+    let mut variables = BTreeMap::from([("HOME".to_string(), home.display().to_string())]);
+    for file in files {
+        for line in fs::read_to_string(&file).unwrap_or_default().lines() {
+            let line = line.trim();
+            let Some((name, value)) = line.split_once('=').filter(|_| !line.starts_with('#'))
+            else {
+                continue;
+            };
+
+            let expanded = expand_variables(value.trim_matches('"'), &variables);
+            variables.insert(name.trim().to_string(), expanded);
+        }
+    }
+
+    [
+        ("config", "XDG_CONFIG_HOME"),
+        ("data", "XDG_DATA_HOME"),
+        ("cache", "XDG_CACHE_HOME"),
+        ("state", "XDG_STATE_HOME"),
+    ]
+    .into_iter()
+    .filter_map(|(xdg_dir, variable)| {
+        let path = PathBuf::from(variables.get(variable)?);
+        // Relative paths are invalid in the XDG specification and ignored.
+        path.is_absolute().then(|| (xdg_dir.to_string(), path))
+    })
+    .collect()
+}
+
+/// Replaces `$NAME` and `${NAME}` with the values of known variables, leaving unknown ones as written.
+fn expand_variables(value: &str, variables: &BTreeMap<String, String>) -> String {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut expanded = String::new();
+    let mut rest = value;
+
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let (name, reference_len) = match after.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            },
+            None => {
+                let end = after.find(|c| !is_name_char(c)).unwrap_or(after.len());
+                (&after[..end], end)
+            }
+        };
+
+        match variables.get(name).filter(|_| !name.is_empty()) {
+            Some(variable_value) => expanded.push_str(variable_value),
+            None => expanded.push_str(&rest[start..start + 1 + reference_len]),
+        }
+        rest = &after[reference_len..];
+    }
+
+    expanded.push_str(rest);
+    expanded
+}
+
+/// Gives a created path to the configured owner, if one is set.
+pub fn set_owner(path: &Path) -> Result<(), Error> {
+    set_owner_to(path, get_path_overrides().owner)
+}
+
+fn set_owner_to(path: &Path, owner: Option<(u32, u32)>) -> Result<(), Error> {
+    match owner {
+        Some((uid, gid)) => chown(path, Some(uid), Some(gid)).map_err(|e| {
+            Error::new(
+                e.kind(),
+                format!("Failed to change owner of {}: {}", path.display(), e),
+            )
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Gives an opened file to the configured owner, if one is set. Unlike [`set_owner`], a
+/// symlink put in its place can't redirect the change.
+pub fn set_file_owner(file: &fs::File, path: &Path) -> Result<(), Error> {
+    match get_path_overrides().owner {
+        Some((uid, gid)) => fchown(file, Some(uid), Some(gid)).map_err(|e| {
+            Error::new(
+                e.kind(),
+                format!("Failed to change owner of {}: {}", path.display(), e),
+            )
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Creates a directory and its missing parents, giving each created one to the configured owner.
+pub fn create_dir_all_owned(path: &Path) -> Result<(), Error> {
+    create_dir_all_owned_by(path, get_path_overrides().owner)
+}
+
+fn create_dir_all_owned_by(path: &Path, owner: Option<(u32, u32)>) -> Result<(), Error> {
+    let missing: Vec<&Path> = path
+        .ancestors()
+        .take_while(|ancestor| !ancestor.as_os_str().is_empty() && !ancestor.exists())
+        .collect();
+
+    fs::create_dir_all(path)
+        .map_err(|e| Error::other(format!("Failed to create directory structure: {}", e)))?;
+
+    missing
+        .into_iter()
+        .rev()
+        .try_for_each(|path| set_owner_to(path, owner))
+}
+
+/// The home directory the XDG directories are resolved from.
+fn get_home_dir() -> Result<PathBuf, Error> {
+    match &get_path_overrides().home {
+        Some(home) => Ok(home.clone()),
+        None => Ok(get_base_strategy()?.home_dir().to_path_buf()),
+    }
+}
+
+/// Expands a leading `~`, `$HOME` or `${HOME}` to the home directory.
+fn expand_home(path: &Path) -> Result<PathBuf, Error> {
+    let mut components = path.components();
+
+    match components.next() {
+        Some(Component::Normal(first))
+            if ["~", "$HOME", "${HOME}"].contains(&first.to_str().unwrap_or_default()) =>
+        {
+            Ok(get_home_dir()?.join(components.as_path()))
+        }
+        _ => Ok(path.to_path_buf()),
+    }
+}
+
+/// Makes a path absolute and resolves `.`, `..` and the symlinks of its existing ancestors.
+pub fn normalize_path(path: &Path) -> Result<PathBuf, Error> {
+    let normalized = remove_dot_components(&env::current_dir()?.join(path));
+
+    let existing = normalized
+        .ancestors()
+        .find(|ancestor| ancestor.exists())
+        .unwrap_or(Path::new("/"));
+    let canonical = fs::canonicalize(existing)?;
+
+    match normalized.strip_prefix(existing) {
+        Ok(missing) if !missing.as_os_str().is_empty() => Ok(canonical.join(missing)),
+        _ => Ok(canonical),
+    }
+}
+
+/// Resolves a direct file path, expanding `~` and confining it to the root directory if one is set.
+pub fn resolve_file_path(file_path: &Path) -> Result<PathBuf, Error> {
+    let file_path = &expand_home(file_path)?;
+    let Some(root) = &get_path_overrides().root else {
+        return Ok(file_path.to_path_buf());
+    };
+
+    let mut resolved = root.clone();
+    for component in file_path.components() {
+        match component {
+            Component::Normal(part) => resolved.push(part),
+            Component::ParentDir if resolved != *root => {
+                resolved.pop();
+            }
+            Component::ParentDir => {
+                return Err(Error::new(
+                    ErrorKind::PermissionDenied,
+                    format!(
+                        "Path {} escapes the root directory {}",
+                        file_path.display(),
+                        root.display()
+                    ),
+                ))
+            }
+            Component::Prefix(_) | Component::RootDir | Component::CurDir => {}
+        }
+    }
+
+    Ok(resolved)
+}
 
 fn get_base_strategy() -> Result<Xdg, Error> {
     choose_base_strategy().map_err(|e| {
@@ -25,10 +498,20 @@ pub fn read_configuration(
     entry: &str,
     xdg_dir: &str,
 ) -> Result<String, Error> {
-    let path = get_configuration_path(component, version, entry, xdg_dir)?;
+    into_utf8(read_raw_configuration(component, version, entry, xdg_dir)?)
+}
+
+/// Reads a value byte for byte, whether or not it is valid UTF-8.
+pub fn read_raw_configuration(
+    component: &str,
+    version: &u64,
+    entry: &str,
+    xdg_dir: &str,
+) -> Result<Vec<u8>, Error> {
+    let path = resolve_symlink(&get_configuration_path(component, version, entry, xdg_dir)?)?;
 
     if path.exists() {
-        fs::read_to_string(path)
+        fs::read(path)
     } else {
         Err(Error::new(
             ErrorKind::NotFound,
@@ -40,9 +523,16 @@ pub fn read_configuration(
     }
 }
 
-pub fn read_configuration_file(file_path: &PathBuf) -> Result<String, Error> {
+pub fn read_configuration_file(file_path: &Path) -> Result<String, Error> {
+    into_utf8(read_raw_configuration_file(file_path)?)
+}
+
+/// Reads a configuration file byte for byte, whether or not it is valid UTF-8.
+pub fn read_raw_configuration_file(file_path: &Path) -> Result<Vec<u8>, Error> {
+    let file_path = &resolve_symlink(&resolve_file_path(file_path)?)?;
+
     if file_path.exists() {
-        fs::read_to_string(file_path)
+        fs::read(file_path)
     } else {
         Err(Error::new(
             ErrorKind::NotFound,
@@ -51,14 +541,23 @@ pub fn read_configuration_file(file_path: &PathBuf) -> Result<String, Error> {
     }
 }
 
+fn into_utf8(bytes: Vec<u8>) -> Result<String, Error> {
+    String::from_utf8(bytes).map_err(|_| {
+        Error::new(
+            ErrorKind::InvalidData,
+            "Value is not valid UTF-8, use --raw to read its bytes",
+        )
+    })
+}
+
 pub fn write_configuration(
     component: &str,
     version: &u64,
     entry: &str,
     value: &str,
     xdg_dir: &str,
+    force: bool,
 ) -> Result<bool, Error> {
-    let path = get_configuration_path(component, version, entry, xdg_dir)?;
     let unescaped_value = unescape(value).map_err(|e| {
         Error::new(
             ErrorKind::InvalidInput,
@@ -66,34 +565,106 @@ pub fn write_configuration(
         )
     })?;
 
-    if let Ok(current_value) = read_configuration(component, version, entry, xdg_dir) {
-        if current_value == unescaped_value {
-            return Ok(false);
-        }
+    write_raw_configuration(
+        component,
+        version,
+        entry,
+        unescaped_value.as_bytes(),
+        xdg_dir,
+        force,
+    )
+}
+
+/// Writes a value byte for byte, without processing escape sequences.
+pub fn write_raw_configuration(
+    component: &str,
+    version: &u64,
+    entry: &str,
+    value: &[u8],
+    xdg_dir: &str,
+    force: bool,
+) -> Result<bool, Error> {
+    let path = get_configuration_path(component, version, entry, xdg_dir)?;
+
+    let _lock = lock_configuration()?;
+    let current_value = read_resolved(&path).ok();
+    if current_value.as_deref() == Some(value) {
+        return Ok(false);
     }
 
     if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).map_err(|e| {
-            Error::new(
-                ErrorKind::Other,
-                format!("Failed to create directory structure: {}", e),
-            )
-        })?;
+        create_dir_all_owned(parent)?;
     }
 
-    let af = AtomicFile::new(&path, OverwriteBehavior::AllowOverwrite);
-    af.write(|f| f.write_all(unescaped_value.as_bytes()))
-        .map_err(|e| {
-            Error::new(
-                ErrorKind::Other,
-                format!("Failed to write configuration to {}: {}", path.display(), e),
-            )
-        })?;
+    replace_file(&path, value, force, get_path_overrides())?;
+
+    record_configuration_change(
+        component,
+        version,
+        entry,
+        xdg_dir,
+        current_value.map(|current| String::from_utf8_lossy(&current).to_string()),
+        Some(String::from_utf8_lossy(value).to_string()),
+    );
+
+    Ok(true)
+}
+
+/// Reads an entry from another user's configuration, as resolved by [`user_path_overrides`].
+pub fn read_raw_user_configuration(
+    overrides: &PathOverrides,
+    component: &str,
+    version: &u64,
+    entry: &str,
+    xdg_dir: &str,
+) -> Result<Vec<u8>, Error> {
+    let path = get_configuration_path_in(overrides, component, version, entry, xdg_dir)?;
+
+    read_resolved(&path).map_err(|e| match e.kind() {
+        ErrorKind::NotFound => Error::new(
+            ErrorKind::NotFound,
+            format!("Configuration entry not found: {}", path.display()),
+        ),
+        _ => e,
+    })
+}
+
+/// Writes an entry into another user's configuration, giving every file and directory
+/// created to them. The journal records the entry by path, since undoing it as one of
+/// the current user's entries would write to the wrong tree.
+pub fn write_raw_user_configuration(
+    overrides: &PathOverrides,
+    component: &str,
+    version: &u64,
+    entry: &str,
+    value: &[u8],
+    xdg_dir: &str,
+    force: bool,
+) -> Result<bool, Error> {
+    let path = get_configuration_path_in(overrides, component, version, entry, xdg_dir)?;
+
+    let _lock = lock_configuration()?;
+    let current_value = read_resolved(&path).ok();
+    if current_value.as_deref() == Some(value) {
+        return Ok(false);
+    }
+
+    if let Some(parent) = path.parent() {
+        create_dir_all_owned_by(parent, overrides.owner)?;
+    }
+
+    replace_file(&path, value, force, overrides)?;
+
+    record_file_change(
+        &path,
+        current_value.map(|current| String::from_utf8_lossy(&current).to_string()),
+        Some(String::from_utf8_lossy(value).to_string()),
+    );
 
     Ok(true)
 }
 
-pub fn write_configuration_file(file_path: &PathBuf, value: &str) -> Result<bool, Error> {
+pub fn write_configuration_file(file_path: &Path, value: &str, force: bool) -> Result<bool, Error> {
     let unescaped_value = unescape(value).map_err(|e| {
         Error::new(
             ErrorKind::InvalidInput,
@@ -101,37 +672,188 @@ pub fn write_configuration_file(file_path: &PathBuf, value: &str) -> Result<bool
         )
     })?;
 
-    if let Ok(current_value) = fs::read_to_string(file_path) {
-        if current_value == unescaped_value {
-            return Ok(false);
-        }
+    write_raw_configuration_file(file_path, unescaped_value.as_bytes(), force)
+}
+
+/// Writes a value to a configuration file byte for byte, without processing escape sequences.
+pub fn write_raw_configuration_file(
+    file_path: &Path,
+    value: &[u8],
+    force: bool,
+) -> Result<bool, Error> {
+    let file_path = &resolve_file_path(file_path)?;
+
+    let _lock = lock_configuration()?;
+    let current_value = read_resolved(file_path).ok();
+    if current_value.as_deref() == Some(value) {
+        return Ok(false);
     }
 
     if let Some(parent) = file_path.parent() {
-        fs::create_dir_all(parent).map_err(|e| {
-            Error::new(
-                ErrorKind::Other,
-                format!("Failed to create directory structure: {}", e),
-            )
-        })?;
+        create_dir_all_owned(parent)?;
     }
 
-    let af = AtomicFile::new(file_path, OverwriteBehavior::AllowOverwrite);
-    af.write(|f| f.write_all(unescaped_value.as_bytes()))
-        .map_err(|e| {
-            Error::new(
-                ErrorKind::Other,
-                format!(
-                    "Failed to write configuration to {}: {}",
-                    file_path.display(),
-                    e
-                ),
-            )
-        })?;
+    replace_file(file_path, value, force, get_path_overrides())?;
+
+    record_file_change(
+        file_path,
+        current_value.map(|current| String::from_utf8_lossy(&current).to_string()),
+        Some(String::from_utf8_lossy(value).to_string()),
+    );
 
     Ok(true)
 }
 
+/// Atomically replaces a file with `value`, keeping the mode, ownership and extended
+/// attributes of the file being replaced. Read-only files are only replaced with `force`.
+/// Symlinks are written through to their targets unless they should be replaced.
+///
+/// The attributes are copied to the new file before it takes the old one's place. Failing to
+/// copy one only warns, since the value matters more. With `--user`, the file is given to
+/// that user rather than keeping its previous owner.
+fn replace_file(
+    path: &Path,
+    value: &[u8],
+    force: bool,
+    overrides: &PathOverrides,
+) -> Result<(), Error> {
+    let is_symlink = fs::symlink_metadata(path).is_ok_and(|metadata| metadata.is_symlink());
+    let path = &if is_symlink && !overrides.replace_symlinks {
+        let target = resolve_symlink(path)?;
+        check_symlink_target(path, &target, overrides)?;
+        target
+    } else {
+        path.to_path_buf()
+    };
+    // A replaced symlink becomes a new file rather than taking over its target's attributes.
+    let existing = fs::symlink_metadata(path)
+        .ok()
+        .filter(|metadata| !metadata.is_symlink());
+
+    if existing
+        .as_ref()
+        .is_some_and(|metadata| metadata.permissions().readonly())
+        && !force
+    {
+        return Err(Error::new(
+            ErrorKind::PermissionDenied,
+            format!(
+                "{} is read-only, use --force to overwrite it",
+                path.display()
+            ),
+        ));
+    }
+
+    let attributes: Vec<_> = xattr::list(path)
+        .into_iter()
+        .flatten()
+        .filter_map(|name| {
+            let value = xattr::get(path, &name).ok().flatten()?;
+            Some((name, value))
+        })
+        .collect();
+    let owner = overrides.owner.or_else(|| {
+        existing
+            .as_ref()
+            .map(|metadata| (metadata.uid(), metadata.gid()))
+    });
+
+    let af = AtomicFile::new(path, OverwriteBehavior::AllowOverwrite);
+    af.write(|f| {
+        f.write_all(value)?;
+        preserve_attributes(f, path, owner, existing.as_ref(), &attributes);
+        Ok::<(), Error>(())
+    })
+    .map_err(|e| {
+        Error::other(format!(
+            "Failed to write configuration to {}: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+/// With `--user`, the replacement is given to that user, so a symlink in their tree is only
+/// written through to a file inside their home that they already own.
+fn check_symlink_target(
+    link: &Path,
+    target: &Path,
+    overrides: &PathOverrides,
+) -> Result<(), Error> {
+    let (Some((uid, _)), Some(home)) = (overrides.owner, &overrides.home) else {
+        return Ok(());
+    };
+
+    let home = under_root(home.clone());
+    let inside_home = match &overrides.root {
+        Some(_) => remove_dot_components(target).starts_with(remove_dot_components(&home)),
+        None => normalize_path(target)?.starts_with(normalize_path(&home)?),
+    };
+    // A target that doesn't exist yet is created in its parent directory.
+    let owned = fs::metadata(target)
+        .or_else(|_| fs::metadata(target.parent().unwrap_or(target)))
+        .is_ok_and(|metadata| metadata.uid() == uid);
+
+    if inside_home && owned {
+        Ok(())
+    } else {
+        Err(Error::new(
+            ErrorKind::PermissionDenied,
+            format!(
+                "Symlink {} points to {}, which is not the user's own file in {}",
+                link.display(),
+                target.display(),
+                home.display()
+            ),
+        ))
+    }
+}
+
+/// Gives the new file of `path` its owner, extended attributes and mode, warning about
+/// the ones that can't be set.
+fn preserve_attributes(
+    file: &fs::File,
+    path: &Path,
+    owner: Option<(u32, u32)>,
+    existing: Option<&fs::Metadata>,
+    attributes: &[(OsString, Vec<u8>)],
+) {
+    let warn = |what: &str, e: Error| {
+        eprintln!(
+            "Warning: failed to preserve the {} of {}: {}",
+            what,
+            path.display(),
+            e
+        );
+    };
+
+    if let Some((uid, gid)) = owner {
+        let current = file
+            .metadata()
+            .map(|metadata| (metadata.uid(), metadata.gid()));
+        if current.ok() != Some((uid, gid)) {
+            if let Err(e) = fchown(file, Some(uid), Some(gid)) {
+                warn("owner", e);
+            }
+        }
+    }
+
+    for (name, value) in attributes {
+        if file.get_xattr(name).ok().flatten().as_ref() == Some(value) {
+            continue;
+        }
+        if let Err(e) = file.set_xattr(name, value) {
+            warn(&format!("extended attribute {}", name.to_string_lossy()), e);
+        }
+    }
+
+    if let Some(metadata) = existing {
+        if let Err(e) = file.set_permissions(metadata.permissions()) {
+            warn("permissions", e);
+        }
+    }
+}
+
 pub fn delete_configuration(
     component: &str,
     version: &u64,
@@ -139,8 +861,14 @@ pub fn delete_configuration(
     xdg_dir: &str,
 ) -> Result<(), Error> {
     let path = get_configuration_path(component, version, entry, xdg_dir)?;
-    if path.exists() {
+    let _lock = lock_configuration()?;
+    // Dangling symlinks don't exist() but can still be removed.
+    if fs::symlink_metadata(&path).is_ok() {
+        let current_value = read_resolved(&path)
+            .ok()
+            .and_then(|value| String::from_utf8(value).ok());
         fs::remove_file(path)?;
+        record_configuration_change(component, version, entry, xdg_dir, current_value, None);
         Ok(())
     } else {
         Err(Error::new(
@@ -150,9 +878,40 @@ pub fn delete_configuration(
     }
 }
 
-pub fn delete_configuration_file(file_path: &PathBuf) -> Result<(), Error> {
-    if file_path.exists() {
+/// Deletes an entry from another user's configuration, recording it by path in the journal.
+pub fn delete_user_configuration(
+    overrides: &PathOverrides,
+    component: &str,
+    version: &u64,
+    entry: &str,
+    xdg_dir: &str,
+) -> Result<(), Error> {
+    let path = get_configuration_path_in(overrides, component, version, entry, xdg_dir)?;
+    let _lock = lock_configuration()?;
+    if fs::symlink_metadata(&path).is_ok() {
+        let current_value = read_resolved(&path)
+            .ok()
+            .and_then(|value| String::from_utf8(value).ok());
+        fs::remove_file(&path)?;
+        record_file_change(&path, current_value, None);
+        Ok(())
+    } else {
+        Err(Error::new(
+            ErrorKind::NotFound,
+            "Configuration entry does not exist",
+        ))
+    }
+}
+
+pub fn delete_configuration_file(file_path: &Path) -> Result<(), Error> {
+    let file_path = &resolve_file_path(file_path)?;
+    let _lock = lock_configuration()?;
+    if fs::symlink_metadata(file_path).is_ok() {
+        let current_value = read_resolved(file_path)
+            .ok()
+            .and_then(|value| String::from_utf8(value).ok());
         fs::remove_file(file_path)?;
+        record_file_change(file_path, current_value, None);
         Ok(())
     } else {
         Err(Error::new(
@@ -162,6 +921,99 @@ pub fn delete_configuration_file(file_path: &PathBuf) -> Result<(), Error> {
     }
 }
 
+/// Lists the components, versions and entries stored in a COSMIC configuration directory.
+pub fn list_configuration_entries(
+    cosmic_path: &Path,
+) -> BTreeMap<String, BTreeMap<u64, BTreeSet<String>>> {
+    let mut components: BTreeMap<String, BTreeMap<u64, BTreeSet<String>>> = BTreeMap::new();
+
+    for component_dir in read_subdirectories(cosmic_path) {
+        let Some(component) = component_dir.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+
+        for version_dir in read_subdirectories(&component_dir) {
+            let Some(version) = parse_version_dir(&version_dir) else {
+                continue;
+            };
+
+            let entries = fs::read_dir(&version_dir)
+                .into_iter()
+                .flatten()
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_file())
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .collect();
+
+            components
+                .entry(component.to_string())
+                .or_default()
+                .insert(version, entries);
+        }
+    }
+
+    components
+}
+
+/// The versions of a component present in the XDG directory, or also in the system defaults for
+/// the config directory.
+pub fn list_component_versions(component: &str, xdg_dir: &str) -> Result<BTreeSet<u64>, Error> {
+    validate_component_name(component)?;
+
+    let mut component_dirs = vec![get_cosmic_configurations(xdg_dir)?.join(component)];
+    // System defaults only exist for the config directory.
+    if xdg_dir == "config" {
+        component_dirs.extend(
+            get_system_default_dirs()
+                .into_iter()
+                .map(|dir| dir.join(component)),
+        );
+    }
+
+    Ok(component_dirs
+        .iter()
+        .flat_map(|dir| read_subdirectories(dir))
+        .filter_map(|version_dir| parse_version_dir(&version_dir))
+        .collect())
+}
+
+/// The version to use when none is given: the highest one present, or 1 for a new component.
+/// Warns when several versions exist, since the others may hold stale values.
+pub fn resolve_latest_version(component: &str, xdg_dir: &str) -> Result<u64, Error> {
+    let versions = list_component_versions(component, xdg_dir)?;
+    let latest = versions.last().copied().unwrap_or(1);
+
+    if versions.len() > 1 {
+        let listed: Vec<String> = versions
+            .iter()
+            .map(|version| format!("v{}", version))
+            .collect();
+        eprintln!(
+            "Warning: {} has configuration versions {}, using v{}.",
+            component,
+            listed.join(", "),
+            latest
+        );
+    }
+
+    Ok(latest)
+}
+
+/// The version of a `vN` directory.
+fn parse_version_dir(path: &Path) -> Option<u64> {
+    path.file_name()?.to_str()?.strip_prefix('v')?.parse().ok()
+}
+
+fn read_subdirectories(path: &Path) -> Vec<PathBuf> {
+    fs::read_dir(path)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect()
+}
+
 pub fn parse_configuration_path(path: &Path) -> Option<(String, u64, String)> {
     let parts: Vec<_> = path.iter().collect();
 
@@ -177,18 +1029,93 @@ pub fn parse_configuration_path(path: &Path) -> Option<(String, u64, String)> {
     Some((component, version, entry_name))
 }
 
+/// Whether a name is made of dot-separated segments, with at least `min_segments`.
+fn is_valid_name(name: &str, min_segments: usize) -> bool {
+    let segments: Vec<&str> = name.split('.').collect();
+
+    segments.len() >= min_segments && segments.iter().all(|segment| is_valid_segment(segment))
+}
+
+/// Whether a name matches `^[a-zA-Z0-9_-]+$`.
+fn is_valid_segment(segment: &str) -> bool {
+    !segment.is_empty()
+        && segment
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Checks that a component name is reverse-DNS-like (e.g., 'com.system76.CosmicComp').
+pub fn validate_component_name(component: &str) -> Result<(), Error> {
+    if is_valid_name(component, 2) {
+        Ok(())
+    } else {
+        Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "Invalid component name '{}': expected a reverse-DNS name like 'com.system76.CosmicComp'",
+                component.escape_debug()
+            ),
+        ))
+    }
+}
+
+/// Checks that an entry name matches the schema's entry names (e.g., 'autotile').
+pub fn validate_entry_name(entry: &str) -> Result<(), Error> {
+    if is_valid_segment(entry) {
+        Ok(())
+    } else {
+        Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "Invalid entry name '{}': only letters, digits, '_' and '-' are allowed",
+                entry.escape_debug()
+            ),
+        ))
+    }
+}
+
 fn get_configuration_path(
     component: &str,
     version: &u64,
     entry: &str,
     xdg_dir: &str,
 ) -> Result<PathBuf, Error> {
-    let cosmic_folder = get_cosmic_configurations(xdg_dir)?;
+    get_configuration_path_in(get_path_overrides(), component, version, entry, xdg_dir)
+}
 
-    Ok(cosmic_folder
+fn get_configuration_path_in(
+    overrides: &PathOverrides,
+    component: &str,
+    version: &u64,
+    entry: &str,
+    xdg_dir: &str,
+) -> Result<PathBuf, Error> {
+    validate_component_name(component)?;
+    validate_entry_name(entry)?;
+
+    let cosmic_folder = overrides.xdg_dir_path(xdg_dir)?.join("cosmic");
+    let path = cosmic_folder
         .join(component)
         .join(format!("v{}", version))
-        .join(entry))
+        .join(entry);
+
+    let stays_inside = path.strip_prefix(&cosmic_folder).is_ok_and(|relative| {
+        relative
+            .components()
+            .all(|part| matches!(part, Component::Normal(_)))
+    });
+    if !stays_inside {
+        return Err(Error::new(
+            ErrorKind::PermissionDenied,
+            format!(
+                "Path {} escapes the COSMIC directory {}",
+                path.display(),
+                cosmic_folder.display()
+            ),
+        ));
+    }
+
+    Ok(path)
 }
 
 pub fn get_cosmic_configurations(xdg_dir: &str) -> Result<PathBuf, Error> {
@@ -196,20 +1123,48 @@ pub fn get_cosmic_configurations(xdg_dir: &str) -> Result<PathBuf, Error> {
     Ok(config_dir)
 }
 
-pub fn get_xdg_dir_path(xdg_dir: &str) -> Result<PathBuf, Error> {
-    match xdg_dir.to_lowercase().as_str() {
-        "config" => Ok(get_base_strategy()?.config_dir()),
-        "data" => Ok(get_base_strategy()?.data_dir()),
-        "cache" => Ok(get_base_strategy()?.cache_dir()),
-        "state" => get_base_strategy()?
-            .state_dir()
-            .ok_or_else(|| Error::new(ErrorKind::NotFound, "State directory is not available")),
-        "runtime" => get_base_strategy()?
-            .runtime_dir()
-            .ok_or_else(|| Error::new(ErrorKind::NotFound, "Runtime directory is not available")),
-        _ => Err(Error::new(
-            ErrorKind::InvalidInput,
-            format!("Invalid XDG directory: {}", xdg_dir),
-        )),
+/// Directories holding the system-wide COSMIC defaults, in lookup order.
+pub fn get_system_default_dirs() -> Vec<PathBuf> {
+    let data_dirs = env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+
+    env::split_paths(&data_dirs)
+        .filter(|path| path.is_absolute())
+        .map(|path| under_root(path.join("cosmic")))
+        .collect()
+}
+
+pub fn get_system_default_path(component: &str, version: &u64, entry: &str) -> Option<PathBuf> {
+    validate_component_name(component).ok()?;
+    validate_entry_name(entry).ok()?;
+
+    get_system_default_dirs()
+        .into_iter()
+        .map(|dir| {
+            dir.join(component)
+                .join(format!("v{}", version))
+                .join(entry)
+        })
+        .find(|path| path.is_file())
+}
+
+/// Compares two entry values, treating them as equal when they parse to the same RON value.
+pub fn is_default_value(value: &str, default: &str) -> bool {
+    if value == default {
+        return true;
     }
+
+    match (
+        ron::from_str::<ron::Value>(value),
+        ron::from_str::<ron::Value>(default),
+    ) {
+        (Ok(value), Ok(default)) => value == default,
+        _ => value.trim() == default.trim(),
+    }
+}
+
+pub fn get_xdg_dir_path(xdg_dir: &str) -> Result<PathBuf, Error> {
+    get_path_overrides().xdg_dir_path(xdg_dir)
 }
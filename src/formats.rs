@@ -1,4 +1,5 @@
 use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
 use std::{
     io::{Error, ErrorKind},
     path::Path,
@@ -10,8 +11,15 @@ pub enum FileFormat {
     Json,
     Toml,
     Ron,
+    /// A Nix attribute set, only written by backups.
+    Nix,
 }
 
+/// Words that have to be quoted when used as Nix attribute names.
+const NIX_KEYWORDS: &[&str] = &[
+    "assert", "else", "if", "in", "inherit", "let", "or", "rec", "then", "with",
+];
+
 impl FileFormat {
     pub fn from_path(path: &Path) -> Result<Self, Error> {
         match path.extension().and_then(|s| s.to_str()) {
@@ -19,6 +27,7 @@ impl FileFormat {
                 "json" => Ok(FileFormat::Json),
                 "toml" => Ok(FileFormat::Toml),
                 "ron" => Ok(FileFormat::Ron),
+                "nix" => Ok(FileFormat::Nix),
                 _ => Err(Error::new(
                     ErrorKind::InvalidInput,
                     format!("Unsupported file format: {}", ext),
@@ -28,12 +37,27 @@ impl FileFormat {
         }
     }
 
+    /// Parses a format name such as 'json', as given to `--format`.
+    pub fn from_name(name: &str) -> Result<Self, Error> {
+        match name.to_lowercase().as_str() {
+            "json" => Ok(FileFormat::Json),
+            "toml" => Ok(FileFormat::Toml),
+            "ron" => Ok(FileFormat::Ron),
+            "nix" => Ok(FileFormat::Nix),
+            _ => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Unsupported format: {}", name),
+            )),
+        }
+    }
+
     /// Human-readable name of the format
     pub fn name(&self) -> &'static str {
         match self {
             FileFormat::Json => "JSON",
             FileFormat::Toml => "TOML",
             FileFormat::Ron => "RON",
+            FileFormat::Nix => "Nix",
         }
     }
 
@@ -49,6 +73,10 @@ impl FileFormat {
             FileFormat::Ron => ron::from_str(data).map_err(|e| {
                 Error::new(ErrorKind::InvalidData, format!("RON parsing error: {}", e))
             }),
+            FileFormat::Nix => Err(Error::new(
+                ErrorKind::Unsupported,
+                "Nix files can't be read, they are only written by backups",
+            )),
         }
     }
 
@@ -76,6 +104,105 @@ impl FileFormat {
                     )
                 })
             }
+            FileFormat::Nix => serde_json::to_value(value)
+                .map(|value| to_nix(&value, 0))
+                .map_err(|e| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!("Nix serialization error: {}", e),
+                    )
+                }),
+        }
+    }
+}
+
+/// Renders a value as a Nix expression nested `depth` levels deep.
+fn to_nix(value: &Value, depth: usize) -> String {
+    let indent = "  ".repeat(depth + 1);
+    let closing_indent = "  ".repeat(depth);
+
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(value) => value.to_string(),
+        Value::Number(value) => value.to_string(),
+        Value::String(value) => nix_string(value, depth),
+        Value::Array(items) if items.is_empty() => "[ ]".to_string(),
+        Value::Array(items) => {
+            let items: String = items
+                .iter()
+                .map(|item| format!("{}{}\n", indent, to_nix(item, depth + 1)))
+                .collect();
+            format!("[\n{}{}]", items, closing_indent)
+        }
+        Value::Object(attributes) if attributes.is_empty() => "{ }".to_string(),
+        Value::Object(attributes) => {
+            let attributes: String = attributes
+                .iter()
+                .map(|(name, value)| {
+                    format!(
+                        "{}{} = {};\n",
+                        indent,
+                        nix_attribute_name(name),
+                        to_nix(value, depth + 1)
+                    )
+                })
+                .collect();
+            format!("{{\n{}{}}}", attributes, closing_indent)
+        }
+    }
+}
+
+/// Leaves identifiers like `v1` or `autotile` bare and quotes everything else.
+fn nix_attribute_name(name: &str) -> String {
+    let is_identifier = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '\''));
+
+    if is_identifier && !NIX_KEYWORDS.contains(&name) {
+        name.to_string()
+    } else {
+        nix_string(name, 0)
+    }
+}
+
+/// Multi-line values become indented strings so RON structs stay readable, unless Nix
+/// would strip some of their whitespace or they contain quotes that would need escaping
+/// next to `''${`; everything else is a double-quoted string.
+fn nix_string(value: &str, depth: usize) -> String {
+    let lines = || value.split('\n');
+    // Nix removes blank lines' spaces and the indentation shared by every line.
+    let fits_indented_string = !value.contains(['\r', '\''])
+        && lines().all(|line| !line.trim_start_matches(' ').is_empty())
+        && lines().any(|line| !line.starts_with(' '));
+
+    if value.contains('\n') && fits_indented_string {
+        let indent = "  ".repeat(depth + 1);
+        let lines: Vec<String> = value
+            .replace("${", "''${")
+            .split('\n')
+            .map(|line| format!("{}{}", indent, line))
+            .collect();
+        return format!("''\n{}''", lines.join("\n"));
+    }
+
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '$' if chars.peek() == Some(&'{') => quoted.push_str("\\$"),
+            c => quoted.push(c),
         }
     }
+    quoted.push('"');
+    quoted
 }
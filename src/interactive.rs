@@ -1,9 +1,18 @@
 use crate::commands::{
-    apply::ApplyCommand, backup::BackupCommand, delete::DeleteCommand, read::ReadCommand,
-    reset::ResetCommand, write::WriteCommand, Command,
+    apply::ApplyCommand, backup::BackupCommand, delete::DeleteCommand, edit::EditCommand,
+    log::LogCommand, read::ReadCommand, reset::ResetCommand, write::WriteCommand, Command,
+};
+use crate::config::{
+    get_cosmic_configurations, get_system_default_dirs, list_configuration_entries,
+    read_configuration,
+};
+use crate::tui::run_tui;
+use inquire::{
+    autocompletion::{Autocomplete, Replacement},
+    CustomUserError, MultiSelect, Select, Text,
 };
-use inquire::{MultiSelect, Select, Text};
 use std::{
+    collections::{BTreeMap, BTreeSet},
     io::{Error, ErrorKind},
     path::PathBuf,
 };
@@ -13,24 +22,147 @@ const XDG_DIRECTORIES: [&str; 5] = ["cache", "config", "data", "runtime", "state
 pub fn run_interactive_mode() -> Result<(), Error> {
     let operation = Select::new(
         "What would you like to do?",
-        vec!["Write", "Read", "Delete", "Apply", "Backup", "Reset"],
+        vec![
+            "Write", "Edit", "Read", "Delete", "Apply", "Backup", "Reset", "Log", "Browse",
+        ],
     )
     .prompt()
     .map_err(|e| Error::new(ErrorKind::Other, e.to_string()))?;
 
     match operation {
         "Write" => interactive_write()?,
+        "Edit" => interactive_edit()?,
         "Read" => interactive_read()?,
         "Delete" => interactive_delete()?,
         "Apply" => interactive_apply()?,
         "Backup" => interactive_backup()?,
         "Reset" => interactive_reset()?,
+        "Log" => interactive_log()?,
+        "Browse" => run_tui(
+            XDG_DIRECTORIES.iter().map(|dir| dir.to_string()).collect(),
+            1,
+        )?,
         _ => unreachable!(),
     }
 
     Ok(())
 }
 
+/// Suggests names containing the input, case-insensitively.
+#[derive(Clone)]
+pub struct NameCompleter {
+    names: Vec<String>,
+}
+
+impl NameCompleter {
+    pub fn new<'a>(names: impl IntoIterator<Item = &'a String>) -> Self {
+        Self {
+            names: names.into_iter().cloned().collect(),
+        }
+    }
+}
+
+impl Autocomplete for NameCompleter {
+    fn get_suggestions(&mut self, input: &str) -> Result<Vec<String>, CustomUserError> {
+        let input = input.to_lowercase();
+
+        Ok(self
+            .names
+            .iter()
+            .filter(|name| name.to_lowercase().contains(&input))
+            .cloned()
+            .collect())
+    }
+
+    fn get_completion(
+        &mut self,
+        input: &str,
+        highlighted_suggestion: Option<String>,
+    ) -> Result<Replacement, CustomUserError> {
+        if highlighted_suggestion.is_some() {
+            return Ok(highlighted_suggestion);
+        }
+
+        let suggestions = self.get_suggestions(input)?;
+        Ok(match suggestions.as_slice() {
+            [only] => Some(only.clone()),
+            _ => None,
+        })
+    }
+}
+
+/// Components, versions and entries in an XDG directory, plus the system defaults for `config`.
+fn scan_configuration_entries(
+    xdg_dir: &str,
+) -> Result<BTreeMap<String, BTreeMap<u64, BTreeSet<String>>>, Error> {
+    let mut components = list_configuration_entries(&get_cosmic_configurations(xdg_dir)?);
+
+    if xdg_dir == "config" {
+        for default_dir in get_system_default_dirs() {
+            for (component, versions) in list_configuration_entries(&default_dir) {
+                for (version, entries) in versions {
+                    components
+                        .entry(component.clone())
+                        .or_default()
+                        .entry(version)
+                        .or_default()
+                        .extend(entries);
+                }
+            }
+        }
+    }
+
+    Ok(components)
+}
+
+/// Asks for a component, version and entry, suggesting the ones that already exist.
+fn prompt_configuration_entry(xdg_dir: &str) -> Result<(String, u64, String), Error> {
+    let components = scan_configuration_entries(xdg_dir)?;
+
+    let component = Text::new("Component:")
+        .with_autocomplete(NameCompleter::new(components.keys()))
+        .prompt()
+        .map_err(|e| Error::other(format!("Input error: {}", e)))?;
+
+    let versions = components.get(&component).cloned().unwrap_or_default();
+    let version_names: Vec<String> = versions
+        .keys()
+        .rev()
+        .map(|version| format!("v{}", version))
+        .collect();
+    let latest = version_names.first().map_or("v1", String::as_str);
+    let version = Text::new("Version:")
+        .with_default(latest)
+        .with_help_message("Pick an existing version or type a new one")
+        .with_autocomplete(NameCompleter::new(&version_names))
+        .prompt()
+        .map_err(|e| Error::other(format!("Input error: {}", e)))?;
+    let version = parse_version(&version)?;
+
+    let entries = versions.get(&version).cloned().unwrap_or_default();
+    let entry = Text::new("Entry:")
+        .with_autocomplete(NameCompleter::new(&entries))
+        .prompt()
+        .map_err(|e| Error::other(format!("Input error: {}", e)))?;
+
+    Ok((component, version, entry))
+}
+
+/// Parses a version typed as `2` or `v2`.
+pub fn parse_version(input: &str) -> Result<u64, Error> {
+    let input = input.trim();
+    input
+        .strip_prefix('v')
+        .unwrap_or(input)
+        .parse::<u64>()
+        .map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("Invalid version number '{}': {}", input, e),
+            )
+        })
+}
+
 fn interactive_write() -> Result<(), Error> {
     let file_or_component = Select::new(
         "Would you like to write to a file or a component?",
@@ -48,48 +180,87 @@ fn interactive_write() -> Result<(), Error> {
             .map_err(|e| Error::new(ErrorKind::Other, format!("Input error: {}", e)))?;
 
         let cmd = WriteCommand {
-            version: 1,
+            version: None,
             component: None,
             entry: None,
             value,
             xdg_dir: "config".to_string(),
             file: Some(PathBuf::from(file)),
+            force: false,
+            check: false,
         };
 
         cmd.execute()
     } else {
-        let component = Text::new("Component:")
-            .prompt()
-            .map_err(|e| Error::new(ErrorKind::Other, format!("Input error: {}", e)))?;
-        let entry = Text::new("Entry:")
-            .prompt()
-            .map_err(|e| Error::new(ErrorKind::Other, format!("Input error: {}", e)))?;
-        let version = Text::new("Version:")
-            .with_default("1")
-            .prompt()
-            .map_err(|e| Error::new(ErrorKind::Other, format!("Input error: {}", e)))?
-            .parse::<u64>()
-            .map_err(|e| {
-                Error::new(
-                    ErrorKind::InvalidInput,
-                    format!("Invalid version number: {}", e),
-                )
-            })?;
         let xdg_dir = Select::new("XDG Directory:", XDG_DIRECTORIES.to_vec())
             .prompt()
             .map_err(|e| Error::new(ErrorKind::Other, e.to_string()))?
             .to_string();
+        let (component, version, entry) = prompt_configuration_entry(&xdg_dir)?;
+
+        match read_configuration(&component, &version, &entry, &xdg_dir) {
+            Ok(current) => println!("Current value: {}", current),
+            Err(_) => println!("No current value."),
+        }
+
         let value = Text::new("Value:")
             .prompt()
             .map_err(|e| Error::new(ErrorKind::Other, format!("Input error: {}", e)))?;
 
         let cmd = WriteCommand {
-            version,
+            version: Some(version),
             component: Some(component),
             entry: Some(entry),
             value,
             xdg_dir,
             file: None,
+            force: false,
+            check: true,
+        };
+
+        cmd.execute()
+    }
+}
+
+fn interactive_edit() -> Result<(), Error> {
+    let file_or_component = Select::new(
+        "Would you like to edit a file or a component?",
+        vec!["Component", "File"],
+    )
+    .prompt()
+    .map_err(|e| Error::other(e.to_string()))?;
+
+    if file_or_component == "File" {
+        let file = Text::new("File path:")
+            .prompt()
+            .map_err(|e| Error::other(format!("Input error: {}", e)))?;
+
+        let cmd = EditCommand {
+            version: None,
+            component: None,
+            entry: None,
+            xdg_dir: "config".to_string(),
+            file: Some(PathBuf::from(file)),
+            yes: false,
+            force: false,
+        };
+
+        cmd.execute()
+    } else {
+        let xdg_dir = Select::new("XDG Directory:", XDG_DIRECTORIES.to_vec())
+            .prompt()
+            .map_err(|e| Error::other(e.to_string()))?
+            .to_string();
+        let (component, version, entry) = prompt_configuration_entry(&xdg_dir)?;
+
+        let cmd = EditCommand {
+            version: Some(version),
+            component: Some(component),
+            entry: Some(entry),
+            xdg_dir,
+            file: None,
+            yes: false,
+            force: false,
         };
 
         cmd.execute()
@@ -110,43 +281,29 @@ fn interactive_read() -> Result<(), Error> {
             .map_err(|e| Error::new(ErrorKind::Other, format!("Input error: {}", e)))?;
 
         let cmd = ReadCommand {
-            version: 1,
+            version: None,
             component: None,
             entry: None,
             xdg_dir: "config".to_string(),
             file: Some(PathBuf::from(file)),
+            raw: false,
         };
 
         cmd.execute()
     } else {
-        let component = Text::new("Component:")
-            .prompt()
-            .map_err(|e| Error::new(ErrorKind::Other, format!("Input error: {}", e)))?;
-        let entry = Text::new("Entry:")
-            .prompt()
-            .map_err(|e| Error::new(ErrorKind::Other, format!("Input error: {}", e)))?;
-        let version = Text::new("Version:")
-            .with_default("1")
-            .prompt()
-            .map_err(|e| Error::new(ErrorKind::Other, format!("Input error: {}", e)))?
-            .parse::<u64>()
-            .map_err(|e| {
-                Error::new(
-                    ErrorKind::InvalidInput,
-                    format!("Invalid version number: {}", e),
-                )
-            })?;
         let xdg_dir = Select::new("XDG Directory:", XDG_DIRECTORIES.to_vec())
             .prompt()
             .map_err(|e| Error::new(ErrorKind::Other, e.to_string()))?
             .to_string();
+        let (component, version, entry) = prompt_configuration_entry(&xdg_dir)?;
 
         let cmd = ReadCommand {
-            version,
+            version: Some(version),
             component: Some(component),
             entry: Some(entry),
             xdg_dir,
             file: None,
+            raw: false,
         };
 
         cmd.execute()
@@ -167,7 +324,7 @@ fn interactive_delete() -> Result<(), Error> {
             .map_err(|e| Error::new(ErrorKind::Other, format!("Input error: {}", e)))?;
 
         let cmd = DeleteCommand {
-            version: 1,
+            version: None,
             component: None,
             entry: None,
             xdg_dir: "config".to_string(),
@@ -176,30 +333,14 @@ fn interactive_delete() -> Result<(), Error> {
 
         cmd.execute()
     } else {
-        let component = Text::new("Component:")
-            .prompt()
-            .map_err(|e| Error::new(ErrorKind::Other, format!("Input error: {}", e)))?;
-        let entry = Text::new("Entry:")
-            .prompt()
-            .map_err(|e| Error::new(ErrorKind::Other, format!("Input error: {}", e)))?;
-        let version = Text::new("Version:")
-            .with_default("1")
-            .prompt()
-            .map_err(|e| Error::new(ErrorKind::Other, format!("Input error: {}", e)))?
-            .parse::<u64>()
-            .map_err(|e| {
-                Error::new(
-                    ErrorKind::InvalidInput,
-                    format!("Invalid version number: {}", e),
-                )
-            })?;
         let xdg_dir = Select::new("XDG Directory:", XDG_DIRECTORIES.to_vec())
             .prompt()
             .map_err(|e| Error::new(ErrorKind::Other, e.to_string()))?
             .to_string();
+        let (component, version, entry) = prompt_configuration_entry(&xdg_dir)?;
 
         let cmd = DeleteCommand {
-            version,
+            version: Some(version),
             component: Some(component),
             entry: Some(entry),
             xdg_dir,
@@ -222,6 +363,11 @@ fn interactive_apply() -> Result<(), Error> {
     let cmd = ApplyCommand {
         file: PathBuf::from(file),
         verbose,
+        ignore_checksums: false,
+        force: false,
+        allow_path: Vec::new(),
+        allow_any_path: false,
+        check: false,
     };
 
     cmd.execute()
@@ -253,17 +399,54 @@ fn interactive_backup() -> Result<(), Error> {
 
     let xdg_dirs: Vec<String> = selected_dirs.into_iter().map(String::from).collect();
 
+    let non_default = Select::new(
+        "Only backup entries that differ from the system defaults?",
+        vec!["No", "Yes"],
+    )
+    .prompt()
+    .map_err(|e| Error::other(e.to_string()))?
+        == "Yes";
+
+    let include = Text::new("Patterns to include (comma-separated, leave empty for all):")
+        .prompt()
+        .map_err(|e| Error::other(format!("Input error: {}", e)))?;
+
+    let include_option = if include.trim().is_empty() {
+        None
+    } else {
+        Some(include)
+    };
+
+    let exclude = Text::new("Patterns to exclude (comma-separated, leave empty for none):")
+        .prompt()
+        .map_err(|e| Error::other(format!("Input error: {}", e)))?;
+
+    let exclude_option = if exclude.trim().is_empty() {
+        None
+    } else {
+        Some(exclude)
+    };
+
     let cmd = BackupCommand {
-        file: PathBuf::from(file),
+        file: Some(PathBuf::from(file)),
         verbose,
         xdg_dirs,
         format: None, // Will be auto-detected from file extension
+        include: include_option,
+        exclude: exclude_option,
+        non_default,
+        report: false,
+        no_metadata: false,
     };
 
     cmd.execute()
 }
 
 fn interactive_reset() -> Result<(), Error> {
+    let only = Text::new("Patterns to reset (comma-separated, leave empty for all):")
+        .prompt()
+        .map_err(|e| Error::other(format!("Input error: {}", e)))?;
+
     let exclude = Text::new("Patterns to exclude (comma-separated, leave empty for none):")
         .prompt()
         .map_err(|e| Error::new(ErrorKind::Other, format!("Input error: {}", e)))?;
@@ -273,6 +456,12 @@ fn interactive_reset() -> Result<(), Error> {
         .map_err(|e| Error::new(ErrorKind::Other, e.to_string()))?
         == "Yes";
 
+    let only_option = if only.trim().is_empty() {
+        None
+    } else {
+        Some(only)
+    };
+
     let exclude_option = if exclude.trim().is_empty() {
         None
     } else {
@@ -298,9 +487,28 @@ fn interactive_reset() -> Result<(), Error> {
     let cmd = ResetCommand {
         force: false, // Let the reset command handle confirmation
         verbose,
+        only: only_option,
         exclude: exclude_option,
+        component: None,
+        version: None,
         xdg_dirs,
     };
 
     cmd.execute()
 }
+
+fn interactive_log() -> Result<(), Error> {
+    let component = Text::new("Component pattern (leave empty for all):")
+        .prompt()
+        .map_err(|e| Error::other(format!("Input error: {}", e)))?;
+    let since = Text::new("Since (e.g., '2h' or '2025-04-12', leave empty for all):")
+        .prompt()
+        .map_err(|e| Error::other(format!("Input error: {}", e)))?;
+
+    let cmd = LogCommand {
+        component: Some(component).filter(|component| !component.trim().is_empty()),
+        since: Some(since).filter(|since| !since.trim().is_empty()),
+    };
+
+    cmd.execute()
+}
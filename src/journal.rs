@@ -0,0 +1,209 @@
+use crate::config::{create_dir_all_owned, get_xdg_dir_path, set_file_owner};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, OpenOptions},
+    io::{Error, ErrorKind, Write},
+    os::unix::fs::OpenOptionsExt,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+    time::SystemTime,
+};
+
+/// A single change made by cosmic-ctl.
+#[derive(Deserialize, Serialize)]
+pub struct JournalRecord {
+    pub timestamp: String,
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xdg_directory: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub component: Option<String>,
+    pub address: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+/// How changes are journaled, set once from the global CLI options.
+pub struct JournalSettings {
+    pub enabled: bool,
+    /// Name of the command making the changes.
+    pub command: String,
+    /// Size in bytes after which the journal is rotated.
+    pub max_size: u64,
+    /// Number of rotated journal files to keep.
+    pub max_files: usize,
+}
+
+impl Default for JournalSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            command: "unknown".to_string(),
+            max_size: 1024 * 1024,
+            max_files: 3,
+        }
+    }
+}
+
+static JOURNAL_SETTINGS: OnceLock<JournalSettings> = OnceLock::new();
+
+pub fn set_journal_settings(settings: JournalSettings) {
+    let _ = JOURNAL_SETTINGS.set(settings);
+}
+
+fn get_journal_settings() -> &'static JournalSettings {
+    JOURNAL_SETTINGS.get_or_init(JournalSettings::default)
+}
+
+pub fn get_journal_path() -> Result<PathBuf, Error> {
+    Ok(get_xdg_dir_path("state")?
+        .join("cosmic-ctl")
+        .join("journal.jsonl"))
+}
+
+fn get_rotated_path(journal_path: &Path, index: usize) -> PathBuf {
+    let mut path = journal_path.as_os_str().to_owned();
+    path.push(format!(".{}", index));
+    PathBuf::from(path)
+}
+
+pub fn record_configuration_change(
+    component: &str,
+    version: &u64,
+    entry: &str,
+    xdg_dir: &str,
+    old_value: Option<String>,
+    new_value: Option<String>,
+) {
+    record(
+        Some(xdg_dir.to_string()),
+        Some(component.to_string()),
+        format!("{}/v{}/{}", component, version, entry),
+        old_value,
+        new_value,
+    );
+}
+
+pub fn record_file_change(file_path: &Path, old_value: Option<String>, new_value: Option<String>) {
+    record(
+        None,
+        None,
+        file_path.display().to_string(),
+        old_value,
+        new_value,
+    );
+}
+
+/// Appends a change to the journal. Failures are reported but never abort the change itself.
+fn record(
+    xdg_directory: Option<String>,
+    component: Option<String>,
+    address: String,
+    old_value: Option<String>,
+    new_value: Option<String>,
+) {
+    let settings = get_journal_settings();
+    if !settings.enabled {
+        return;
+    }
+
+    let record = JournalRecord {
+        timestamp: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+        command: settings.command.clone(),
+        xdg_directory,
+        component,
+        address,
+        old_value,
+        new_value,
+    };
+
+    if let Err(e) = append(&record, settings) {
+        eprintln!("Warning: failed to record change in journal: {}", e);
+    }
+}
+
+fn append(record: &JournalRecord, settings: &JournalSettings) -> Result<(), Error> {
+    let journal_path = get_journal_path()?;
+
+    if let Some(parent) = journal_path.parent() {
+        create_dir_all_owned(parent)?;
+    }
+
+    if fs::metadata(&journal_path).is_ok_and(|metadata| metadata.len() >= settings.max_size) {
+        rotate(&journal_path, settings.max_files)?;
+    }
+
+    let mut line = serde_json::to_string(record).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("JSON serialization error: {}", e),
+        )
+    })?;
+    line.push('\n');
+
+    // A symlinked journal is refused rather than appending to, and chowning, its target.
+    let created = !journal_path.exists();
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .custom_flags(libc::O_NOFOLLOW)
+        .open(&journal_path)?;
+    file.write_all(line.as_bytes())?;
+
+    if created {
+        set_file_owner(&file, &journal_path)?;
+    }
+
+    Ok(())
+}
+
+/// Shifts `journal.jsonl.N` to `journal.jsonl.N+1`, dropping the oldest beyond `max_files`.
+fn rotate(journal_path: &Path, max_files: usize) -> Result<(), Error> {
+    if max_files == 0 {
+        return fs::remove_file(journal_path);
+    }
+
+    let oldest = get_rotated_path(journal_path, max_files);
+    if oldest.exists() {
+        fs::remove_file(oldest)?;
+    }
+
+    for index in (1..max_files).rev() {
+        let rotated = get_rotated_path(journal_path, index);
+        if rotated.exists() {
+            fs::rename(&rotated, get_rotated_path(journal_path, index + 1))?;
+        }
+    }
+
+    fs::rename(journal_path, get_rotated_path(journal_path, 1))
+}
+
+/// Reads every journal record, oldest first.
+pub fn read_journal() -> Result<Vec<JournalRecord>, Error> {
+    let journal_path = get_journal_path()?;
+    let mut paths: Vec<PathBuf> = (1..)
+        .map(|index| get_rotated_path(&journal_path, index))
+        .take_while(|path| path.exists())
+        .collect();
+    paths.reverse();
+    paths.push(journal_path);
+
+    let mut records = Vec::new();
+    for path in paths.iter().filter(|path| path.exists()) {
+        for line in fs::read_to_string(path)?.lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let record = serde_json::from_str(line).map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Invalid journal record in {}: {}", path.display(), e),
+                )
+            })?;
+            records.push(record);
+        }
+    }
+
+    Ok(records)
+}
@@ -0,0 +1,134 @@
+use crate::config::get_own_xdg_dir_path;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{Error, ErrorKind},
+    os::unix::{fs::OpenOptionsExt, io::AsRawFd},
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock, PoisonError},
+    thread,
+    time::{Duration, Instant},
+};
+
+const RETRY_INTERVAL: Duration = Duration::from_millis(50);
+/// How long to retry without `--wait`, so brief changes by other processes don't cause failures.
+const GRACE_PERIOD: Duration = Duration::from_secs(1);
+
+/// How to wait for the configuration lock, set once from the global CLI options.
+#[derive(Default)]
+pub struct LockSettings {
+    /// Wait for another process to release the lock instead of failing.
+    pub wait: bool,
+    /// Give up waiting after this long.
+    pub timeout: Option<Duration>,
+}
+
+/// The lock file held by this process and how many guards are using it.
+struct HeldLock {
+    file: File,
+    count: usize,
+}
+
+static LOCK_SETTINGS: OnceLock<LockSettings> = OnceLock::new();
+static HELD_LOCK: Mutex<Option<HeldLock>> = Mutex::new(None);
+
+pub fn set_lock_settings(settings: LockSettings) {
+    let _ = LOCK_SETTINGS.set(settings);
+}
+
+fn get_lock_settings() -> &'static LockSettings {
+    LOCK_SETTINGS.get_or_init(LockSettings::default)
+}
+
+/// The lock file lives in the XDG runtime directory, or the state directory when there is none.
+/// Both are the invoking user's own, so nothing is left behind in a `--root` or `--home` tree.
+pub fn get_lock_path() -> Result<PathBuf, Error> {
+    let dir = get_own_xdg_dir_path("runtime").or_else(|_| get_own_xdg_dir_path("state"))?;
+    Ok(dir.join("cosmic-ctl").join("lock"))
+}
+
+/// Keeps the configuration locked until every guard of this process is dropped.
+pub struct LockGuard {
+    _private: (),
+}
+
+impl Drop for LockGuard {
+    fn drop(&mut self) {
+        let mut held = HELD_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if let Some(lock) = held.as_mut() {
+            lock.count -= 1;
+            if lock.count == 0 {
+                let _ = flock(&lock.file, libc::LOCK_UN);
+                *held = None;
+            }
+        }
+    }
+}
+
+/// Takes the advisory lock that serialises configuration changes between cosmic-ctl processes.
+/// Taking it again while this process holds it succeeds immediately.
+pub fn lock_configuration() -> Result<LockGuard, Error> {
+    let mut held = HELD_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+
+    if let Some(lock) = held.as_mut() {
+        lock.count += 1;
+        return Ok(LockGuard { _private: () });
+    }
+
+    let lock_path = get_lock_path()?;
+    if let Some(parent) = lock_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // A symlinked lock file is refused rather than creating its target.
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .custom_flags(libc::O_NOFOLLOW)
+        .open(&lock_path)?;
+
+    acquire(&file, &lock_path, get_lock_settings())?;
+    *held = Some(HeldLock { file, count: 1 });
+
+    Ok(LockGuard { _private: () })
+}
+
+fn acquire(file: &File, lock_path: &Path, settings: &LockSettings) -> Result<(), Error> {
+    let started = Instant::now();
+
+    loop {
+        match flock(file, libc::LOCK_EX | libc::LOCK_NB) {
+            Ok(()) => return Ok(()),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+            Err(e) => return Err(e),
+        }
+
+        let elapsed = started.elapsed();
+        let message = match settings.timeout {
+            Some(timeout) if elapsed >= timeout => format!(
+                "Timed out after {} waiting for another cosmic-ctl process to release {}",
+                humantime::format_duration(timeout),
+                lock_path.display()
+            ),
+            None if !settings.wait && elapsed >= GRACE_PERIOD => format!(
+                "Another cosmic-ctl process is changing the configuration (lock: {}), use --wait to wait for it",
+                lock_path.display()
+            ),
+            _ => {
+                thread::sleep(RETRY_INTERVAL);
+                continue;
+            }
+        };
+
+        return Err(Error::new(ErrorKind::WouldBlock, message));
+    }
+}
+
+fn flock(file: &File, operation: libc::c_int) -> Result<(), Error> {
+    // SAFETY: the descriptor belongs to `file`, which stays open for the call.
+    match unsafe { libc::flock(file.as_raw_fd(), operation) } {
+        0 => Ok(()),
+        _ => Err(Error::last_os_error()),
+    }
+}
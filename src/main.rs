@@ -1,31 +1,124 @@
+mod catalog;
 mod commands;
 mod config;
 mod formats;
 mod interactive;
+mod journal;
+mod lock;
+mod metadata;
+mod scan;
 mod schema;
 #[cfg(test)]
 mod tests;
+mod tui;
 mod utils;
 
-use crate::{commands::Commands, interactive::run_interactive_mode};
+use crate::{
+    commands::Commands,
+    config::{set_path_overrides, PathOverrides},
+    interactive::run_interactive_mode,
+    journal::{set_journal_settings, JournalSettings},
+    lock::{set_lock_settings, LockSettings},
+};
 use clap::Parser;
+use std::{collections::BTreeMap, io::Error, path::PathBuf, time::Duration};
 
 /// CLI for COSMIC Desktop configuration management
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 #[command(propagate_version = true)]
 struct Cli {
+    /// Resolve every path inside this directory (e.g., a mounted image).
+    #[arg(long, global = true)]
+    root: Option<PathBuf>,
+    /// Home directory to resolve the XDG directories from (e.g., '/etc/skel').
+    #[arg(long, global = true)]
+    home: Option<PathBuf>,
+    /// Operate on this user's configuration, giving them ownership of created files.
+    #[arg(long, global = true, conflicts_with = "home")]
+    user: Option<String>,
+    /// Write through symlinked entries to their targets (default).
+    #[arg(long, global = true, conflicts_with = "replace_symlinks")]
+    follow_symlinks: bool,
+    /// Replace symlinked entries with regular files instead of writing to their targets.
+    #[arg(long, global = true)]
+    replace_symlinks: bool,
+    /// Do not record changes in the journal.
+    #[arg(long, global = true, env = "COSMIC_CTL_NO_JOURNAL")]
+    no_journal: bool,
+    /// Size in bytes after which the journal is rotated.
+    #[arg(
+        long,
+        global = true,
+        env = "COSMIC_CTL_JOURNAL_MAX_SIZE",
+        default_value_t = 1024 * 1024
+    )]
+    journal_max_size: u64,
+    /// Number of rotated journal files to keep.
+    #[arg(
+        long,
+        global = true,
+        env = "COSMIC_CTL_JOURNAL_MAX_FILES",
+        default_value_t = 3
+    )]
+    journal_max_files: usize,
+    /// Wait for other cosmic-ctl processes to finish changing the configuration.
+    #[arg(long, global = true)]
+    wait: bool,
+    /// Stop waiting for other cosmic-ctl processes after this long (e.g., '30s').
+    #[arg(long, global = true, value_parser = humantime::parse_duration)]
+    timeout: Option<Duration>,
     #[command(subcommand)]
     command: Option<Commands>,
 }
 
+fn configure_paths(
+    root: Option<PathBuf>,
+    home: Option<PathBuf>,
+    user: Option<String>,
+    replace_symlinks: bool,
+) -> Result<(), Error> {
+    let overrides = match user {
+        Some(user) => PathOverrides::for_user(&user, root, replace_symlinks)?,
+        None => PathOverrides {
+            root,
+            home,
+            owner: None,
+            xdg_dirs: BTreeMap::new(),
+            replace_symlinks,
+        },
+    };
+
+    set_path_overrides(overrides);
+    Ok(())
+}
+
 fn main() {
     let cli = Cli::parse();
 
-    let result = match cli.command {
-        Some(cmd) => cmd.execute(),
-        None => run_interactive_mode(),
-    };
+    set_journal_settings(JournalSettings {
+        // Changes inside another tree (e.g., an image or /etc/skel) aren't the invoking
+        // user's history, and their journal would be left behind in that tree.
+        enabled: !cli.no_journal && cli.root.is_none() && cli.home.is_none(),
+        command: cli
+            .command
+            .as_ref()
+            .map_or("interactive", Commands::name)
+            .to_string(),
+        max_size: cli.journal_max_size,
+        max_files: cli.journal_max_files,
+    });
+    set_lock_settings(LockSettings {
+        wait: cli.wait || cli.timeout.is_some(),
+        timeout: cli.timeout,
+    });
+
+    let result = configure_paths(cli.root, cli.home, cli.user, cli.replace_symlinks).and_then(
+        |()| match cli.command {
+            Some(cmd) => cmd.execute(),
+            None => run_interactive_mode(),
+        },
+    );
 
     if let Err(e) = result {
         eprintln!("Error: {}", e);
@@ -0,0 +1,112 @@
+use crate::schema::{Entry, Metadata};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{Error, ErrorKind},
+    time::SystemTime,
+};
+
+/// Version of the metadata block written by this release.
+pub const METADATA_SCHEMA_VERSION: u32 = 1;
+
+pub fn create_metadata(operations: &[Entry]) -> Result<Metadata, Error> {
+    Ok(Metadata {
+        schema_version: METADATA_SCHEMA_VERSION,
+        created_at: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+        hostname: get_hostname(),
+        tool_version: env!("CARGO_PKG_VERSION").to_string(),
+        system: get_system_name(),
+        checksum: Some(compute_checksum(operations)?),
+    })
+}
+
+/// Checks a backup's metadata before applying it, failing on checksum mismatches
+/// and warning about backups from other hosts or older releases.
+pub fn verify_metadata(
+    metadata: &Metadata,
+    operations: &[Entry],
+    ignore_checksums: bool,
+) -> Result<(), Error> {
+    if let (Some(checksum), false) = (&metadata.checksum, ignore_checksums) {
+        if *checksum != compute_checksum(operations)? {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Checksum mismatch: the backup was modified or is corrupted (use --ignore-checksums to apply it anyway)",
+            ));
+        }
+    }
+
+    if let (Some(backup_host), Some(current_host)) = (&metadata.hostname, get_hostname()) {
+        if *backup_host != current_host {
+            eprintln!(
+                "Warning: backup was created on host '{}', restoring on '{}'.",
+                backup_host, current_host
+            );
+        }
+    }
+
+    if metadata.schema_version < METADATA_SCHEMA_VERSION {
+        eprintln!(
+            "Warning: backup was created by cosmic-ctl {} with an older schema version ({} < {}).",
+            metadata.tool_version, metadata.schema_version, METADATA_SCHEMA_VERSION
+        );
+    }
+
+    Ok(())
+}
+
+/// SHA-256 of the operations serialized as JSON with sorted keys, so it is
+/// independent of the file format and of map ordering.
+pub fn compute_checksum(operations: &[Entry]) -> Result<String, Error> {
+    let value = serde_json::to_value(operations).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("JSON serialization error: {}", e),
+        )
+    })?;
+    let canonical = serde_json::to_string(&sort_keys(value)).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("JSON serialization error: {}", e),
+        )
+    })?;
+
+    let digest = Sha256::digest(canonical.as_bytes());
+    let hex: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+
+    Ok(format!("sha256:{}", hex))
+}
+
+fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| (key, sort_keys(value)))
+                .collect::<BTreeMap<_, _>>()
+                .into_iter()
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(values.into_iter().map(sort_keys).collect()),
+        value => value,
+    }
+}
+
+fn get_hostname() -> Option<String> {
+    ["/proc/sys/kernel/hostname", "/etc/hostname"]
+        .iter()
+        .find_map(|path| fs::read_to_string(path).ok())
+        .map(|hostname| hostname.trim().to_string())
+        .filter(|hostname| !hostname.is_empty())
+}
+
+/// COSMIC has no runtime version query, so the OS release it ships with is recorded instead.
+fn get_system_name() -> Option<String> {
+    let os_release = fs::read_to_string("/etc/os-release").ok()?;
+
+    os_release
+        .lines()
+        .find_map(|line| line.strip_prefix("PRETTY_NAME="))
+        .map(|name| name.trim_matches('"').to_string())
+}
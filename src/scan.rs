@@ -0,0 +1,172 @@
+use crate::config::{
+    get_cosmic_configurations, parse_configuration_path, read_resolved, resolve_symlink,
+    validate_component_name, validate_entry_name,
+};
+use rayon::prelude::*;
+use std::{
+    fs,
+    io::Error,
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+};
+
+/// A configuration entry found while walking a cosmic directory.
+pub struct ScannedEntry {
+    pub component: String,
+    pub version: u64,
+    pub entry_name: String,
+    pub path: PathBuf,
+    /// The `component/vN/entry` address of the entry.
+    pub relative_path: String,
+    /// Where the entry points to, if it is a symlink.
+    pub symlink_target: Option<PathBuf>,
+}
+
+/// The entries of one XDG directory, sorted by path.
+pub struct ScannedDirectory {
+    pub xdg_dir: String,
+    /// `None` if the directory does not exist.
+    pub entries: Option<Vec<ScannedEntry>>,
+    /// Symlinked directories that were not descended into.
+    pub symlinked_dirs: Vec<PathBuf>,
+}
+
+/// Walks the cosmic directory of each XDG directory in parallel, in the order given.
+/// Symlinked directories are only descended into with `follow_links`.
+pub fn scan_xdg_dirs(
+    xdg_dirs: &[String],
+    follow_links: bool,
+) -> Result<Vec<ScannedDirectory>, Error> {
+    let cosmic_paths = xdg_dirs
+        .iter()
+        .map(|xdg_dir| get_cosmic_configurations(xdg_dir))
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    Ok(xdg_dirs
+        .par_iter()
+        .zip(cosmic_paths)
+        .map(|(xdg_dir, cosmic_path)| scan_directory(xdg_dir, &cosmic_path, follow_links))
+        .collect())
+}
+
+/// Walks a single directory of configuration entries, such as the system defaults.
+/// Symlinked directories are only descended into with `follow_links`.
+pub fn scan_directory(xdg_dir: &str, cosmic_path: &Path, follow_links: bool) -> ScannedDirectory {
+    let mut scanned = ScannedDirectory {
+        xdg_dir: xdg_dir.to_string(),
+        entries: None,
+        symlinked_dirs: Vec::new(),
+    };
+
+    if !cosmic_path.exists() {
+        return scanned;
+    }
+
+    let mut entries = Vec::new();
+    let mut walk = Walk {
+        follow_links,
+        ancestors: Vec::new(),
+        symlinked_dirs: &mut scanned.symlinked_dirs,
+        entries: &mut entries,
+    };
+    walk.directory(cosmic_path, cosmic_path);
+
+    scanned.entries = Some(entries);
+    scanned
+}
+
+/// A walk of a cosmic directory. Symlinks are resolved with [`resolve_symlink`], so with
+/// a root directory set they are never followed outside of it.
+struct Walk<'a> {
+    follow_links: bool,
+    /// Device and inode of the directories being walked, to stop at symlink loops.
+    ancestors: Vec<(u64, u64)>,
+    symlinked_dirs: &'a mut Vec<PathBuf>,
+    entries: &'a mut Vec<ScannedEntry>,
+}
+
+impl Walk<'_> {
+    /// Walks `dir`, which appears as `tree_path` in the walked tree. The two differ inside
+    /// followed symlinks, where entries are read from their targets but named after the tree.
+    fn directory(&mut self, tree_path: &Path, dir: &Path) {
+        let Ok(metadata) = fs::metadata(dir) else {
+            return;
+        };
+        let id = (metadata.dev(), metadata.ino());
+        if self.ancestors.contains(&id) {
+            return;
+        }
+
+        let mut children: Vec<_> = fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|child| child.ok())
+            .map(|child| child.file_name())
+            .collect();
+        children.sort();
+
+        self.ancestors.push(id);
+        for name in children {
+            self.child(&tree_path.join(&name), &dir.join(&name));
+        }
+        self.ancestors.pop();
+    }
+
+    fn child(&mut self, tree_path: &Path, path: &Path) {
+        let Ok(metadata) = fs::symlink_metadata(path) else {
+            return;
+        };
+
+        if !metadata.is_symlink() {
+            if metadata.is_dir() {
+                self.directory(tree_path, path);
+            } else if metadata.is_file() {
+                self.push(tree_path, path, None);
+            }
+            return;
+        }
+
+        // Dangling symlinks, and those pointing outside the root, are kept so reset can
+        // remove them and reading them reports why they can't be read.
+        let target = resolve_symlink(path).ok().filter(|target| target.exists());
+        match target {
+            Some(target) if target.is_dir() && self.follow_links => {
+                self.directory(tree_path, &target)
+            }
+            Some(target) if target.is_dir() => self.symlinked_dirs.push(path.to_path_buf()),
+            Some(target) if !target.is_file() => {}
+            _ => self.push(tree_path, path, fs::read_link(path).ok()),
+        }
+    }
+
+    fn push(&mut self, tree_path: &Path, path: &Path, symlink_target: Option<PathBuf>) {
+        let Some((component, version, entry_name)) = parse_configuration_path(tree_path) else {
+            return;
+        };
+
+        self.entries.push(ScannedEntry {
+            relative_path: format!("{}/v{}/{}", component, version, entry_name),
+            symlink_target,
+            component,
+            version,
+            entry_name,
+            path: path.to_path_buf(),
+        });
+    }
+}
+
+/// Reads the entries in parallel straight from their walked paths, keeping their order.
+/// Entries with invalid names fail like they would through `read_raw_configuration`.
+pub fn read_entries<'a>(
+    entries: impl IntoParallelIterator<Item = &'a ScannedEntry>,
+) -> Vec<(&'a ScannedEntry, Result<Vec<u8>, Error>)> {
+    entries
+        .into_par_iter()
+        .map(|entry| {
+            let value = validate_component_name(&entry.component)
+                .and_then(|()| validate_entry_name(&entry.entry_name))
+                .and_then(|()| read_resolved(&entry.path));
+            (entry, value)
+        })
+        .collect()
+}
@@ -1,7 +1,13 @@
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use base64::{prelude::BASE64_STANDARD, Engine};
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::io::{Error, ErrorKind};
 use std::path::PathBuf;
 
+/// The JSON schema referenced by the `$schema` field of JSON and TOML files.
+pub const SCHEMA_URL: &str =
+    "https://raw.githubusercontent.com/cosmic-utils/cosmic-ctl/refs/heads/main/schema.json";
+
 #[derive(Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Operation {
@@ -10,10 +16,54 @@ pub enum Operation {
     Delete,
 }
 
+/// How the values of an operation are encoded.
+#[derive(Clone, Copy, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    /// Values are base64, used for entries that are not valid UTF-8.
+    Base64,
+}
+
+impl Encoding {
+    /// Decodes a value into the bytes to write.
+    pub fn decode(self, value: &str) -> Result<Vec<u8>, Error> {
+        match self {
+            Encoding::Base64 => BASE64_STANDARD.decode(value).map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Invalid base64 value: {}", e),
+                )
+            }),
+        }
+    }
+}
+
+/// The configuration version of an operation: a number, or `"latest"` for the highest one present.
+#[derive(Clone, Copy, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum VersionSpec {
+    Number(u64),
+    Latest(LatestVersion),
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LatestVersion {
+    Latest,
+}
+
+/// Written as the string `"latest"`, as RON would otherwise write an identifier that the
+/// untagged `VersionSpec` can't read back.
+impl Serialize for LatestVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str("latest")
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 #[serde(untagged)]
 pub enum EntryContent {
-    WriteEntries(HashMap<String, String>),
+    WriteEntries(BTreeMap<String, String>),
     ReadDeleteEntries(Vec<String>),
 }
 
@@ -22,7 +72,7 @@ pub struct Entry {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub component: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub version: Option<u64>,
+    pub version: Option<VersionSpec>,
     pub operation: Operation,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub entries: Option<EntryContent>,
@@ -32,11 +82,29 @@ pub struct Entry {
     pub file: Option<PathBuf>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub value: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<Encoding>,
+}
+
+/// Information about how and where a backup was created.
+#[derive(Deserialize, Serialize)]
+pub struct Metadata {
+    pub schema_version: u32,
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    pub tool_version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
 }
 
 #[derive(Deserialize, Serialize)]
 pub struct ConfigFile {
     #[serde(rename = "$schema", skip_serializing_if = "Option::is_none")]
     pub schema: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Metadata>,
     pub operations: Vec<Entry>,
 }
@@ -0,0 +1,93 @@
+use crate::catalog::{load_catalog, EntryType};
+use std::collections::HashSet;
+
+#[test]
+fn test_catalog_parses() {
+    let catalog = load_catalog().unwrap();
+    assert!(!catalog.components.is_empty());
+}
+
+#[test]
+fn test_catalog_is_consistent() {
+    let catalog = load_catalog().unwrap();
+    let mut components = HashSet::new();
+
+    for component in &catalog.components {
+        assert!(
+            components.insert(&component.name),
+            "{} is listed twice",
+            component.name
+        );
+
+        let mut versions = HashSet::new();
+        for version in &component.versions {
+            assert!(
+                versions.insert(version.version),
+                "{}/v{} is listed twice",
+                component.name,
+                version.version
+            );
+
+            let mut entries = HashSet::new();
+            for entry in &version.entries {
+                let address = format!("{}/v{}/{}", component.name, version.version, entry.name);
+                assert!(entries.insert(&entry.name), "{} is listed twice", address);
+                assert_eq!(
+                    entry.kind == EntryType::Enum,
+                    !entry.values.is_empty(),
+                    "{} must list values if and only if it is an Enum",
+                    address
+                );
+                if let Some(default) = &entry.default {
+                    if let Err(e) = entry.check_value(default) {
+                        panic!("Default of {} doesn't match its type: {}", address, e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_catalog_check_entry() {
+    let catalog = load_catalog().unwrap();
+    let comp = "com.system76.CosmicComp";
+
+    assert_eq!(catalog.check_entry(comp, 1, "autotile", Some("true")), None);
+    assert_eq!(
+        catalog.check_entry(comp, 1, "autotile_behavior", Some("PerWorkspace")),
+        None
+    );
+    assert_eq!(
+        catalog.check_entry(
+            comp,
+            1,
+            "xkb_config",
+            Some("(rules: \"\", layout: \"us\", repeat_delay: 600)")
+        ),
+        None
+    );
+    assert_eq!(
+        catalog.check_entry(comp, 1, "focus_follows_cursor_delay", Some("1.5")),
+        Some(format!(
+            "{}/v1/focus_follows_cursor_delay expects Integer: got '1.5'",
+            comp
+        ))
+    );
+    assert_eq!(
+        catalog.check_entry(comp, 1, "autotile_behavior", Some("Sometimes")),
+        Some(format!(
+            "{}/v1/autotile_behavior expects Enum: 'Sometimes' is not one of Global, PerWorkspace",
+            comp
+        ))
+    );
+    assert_eq!(
+        catalog.check_entry(comp, 1, "autotiles", Some("true")),
+        Some(format!("Unknown entry {}/v1/autotiles", comp))
+    );
+    assert_eq!(
+        catalog.check_entry(comp, 7, "autotile", None),
+        Some(format!("Unknown version {}/v7", comp))
+    );
+    assert_eq!(catalog.check_entry(comp, 1, "autotile", None), None);
+}
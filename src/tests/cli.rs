@@ -32,6 +32,8 @@ fn test_write_command() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -49,6 +51,8 @@ fn test_write_command() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -82,6 +86,8 @@ fn test_read_command() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -98,6 +104,8 @@ fn test_read_command() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             READ_OPERATION,
@@ -120,6 +128,8 @@ fn test_delete_command() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -136,6 +146,8 @@ fn test_delete_command() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             DELETE_OPERATION,
@@ -190,6 +202,8 @@ fn test_apply_command() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .arg(APPLY_OPERATION)
         .arg(config_file)
@@ -251,6 +265,8 @@ fn test_apply_command_verbose() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([APPLY_OPERATION, "--verbose"])
         .arg(&config_file)
@@ -262,6 +278,8 @@ fn test_apply_command_verbose() {
 
     let output = Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([APPLY_OPERATION, "--verbose"])
         .arg(&config_file)
@@ -307,6 +325,8 @@ fn test_backup_command() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -325,6 +345,8 @@ fn test_backup_command() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .arg(BACKUP_OPERATION)
         .arg(&backup_file)
@@ -347,6 +369,8 @@ fn test_backup_command_verbose() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -365,6 +389,8 @@ fn test_backup_command_verbose() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([BACKUP_OPERATION, "--verbose"])
         .arg(&backup_file)
@@ -390,6 +416,8 @@ fn test_reset_command() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -406,6 +434,8 @@ fn test_reset_command() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -438,6 +468,8 @@ fn test_reset_command() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args(["reset", "--force"])
         .assert()
@@ -460,6 +492,8 @@ fn test_reset_command_verbose() {
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
         .env("XDG_CONFIG_HOME", config_home)
+        .env("XDG_STATE_HOME", state_home)
+        .env("XDG_RUNTIME_DIR", state_home)
         .args([
             WRITE_OPERATION,
             "--version",
@@ -486,6 +520,7 @@ fn test_reset_command_verbose() {
         .unwrap()
         .env("XDG_CONFIG_HOME", config_home)
         .env("XDG_STATE_HOME", state_home)
+        .env("XDG_RUNTIME_DIR", state_home)
         .args(["reset", "--force", "--verbose"])
         .assert()
         .success()
@@ -508,6 +543,8 @@ fn test_reset_command_empty_config() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args(["reset", "--force"])
         .assert()
@@ -522,6 +559,8 @@ fn test_reset_command_with_exclude() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -538,6 +577,8 @@ fn test_reset_command_with_exclude() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -554,6 +595,8 @@ fn test_reset_command_with_exclude() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -595,6 +638,8 @@ fn test_reset_command_with_exclude() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             "reset",
@@ -617,6 +662,8 @@ fn test_reset_command_with_exclude_entire_component() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -633,6 +680,8 @@ fn test_reset_command_with_exclude_entire_component() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -649,6 +698,8 @@ fn test_reset_command_with_exclude_entire_component() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -690,6 +741,8 @@ fn test_reset_command_with_exclude_entire_component() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args(["reset", "--force", "--exclude", &format!("{}", COSMIC_COMP)])
         .assert()
@@ -707,6 +760,8 @@ fn test_reset_command_with_exclude_component_version() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -723,6 +778,8 @@ fn test_reset_command_with_exclude_component_version() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -739,6 +796,8 @@ fn test_reset_command_with_exclude_component_version() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -780,6 +839,8 @@ fn test_reset_command_with_exclude_component_version() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             "reset",
@@ -802,6 +863,8 @@ fn test_reset_command_with_exclude_brace_expansion() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -818,6 +881,8 @@ fn test_reset_command_with_exclude_brace_expansion() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -834,6 +899,8 @@ fn test_reset_command_with_exclude_brace_expansion() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -875,6 +942,8 @@ fn test_reset_command_with_exclude_brace_expansion() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             "reset",
@@ -905,6 +974,8 @@ fn test_reset_command_with_exclude_with_wildcard() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -921,6 +992,8 @@ fn test_reset_command_with_exclude_with_wildcard() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -937,6 +1010,8 @@ fn test_reset_command_with_exclude_with_wildcard() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -978,6 +1053,8 @@ fn test_reset_command_with_exclude_with_wildcard() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             "reset",
@@ -1000,6 +1077,8 @@ fn test_reset_command_with_exclude_with_brace_expansion_and_wildcard() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -1016,6 +1095,8 @@ fn test_reset_command_with_exclude_with_brace_expansion_and_wildcard() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -1032,6 +1113,8 @@ fn test_reset_command_with_exclude_with_brace_expansion_and_wildcard() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -1048,6 +1131,8 @@ fn test_reset_command_with_exclude_with_brace_expansion_and_wildcard() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -1097,6 +1182,8 @@ fn test_reset_command_with_exclude_with_brace_expansion_and_wildcard() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             "reset",
@@ -1169,6 +1256,8 @@ fn test_apply_command_toml() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .arg(APPLY_OPERATION)
         .arg(config_file)
@@ -1205,10 +1294,10 @@ fn test_apply_command_ron() {
     let temp_dir = TempDir::new().unwrap();
     let config_home = temp_dir.path().to_str().unwrap();
 
-    use crate::schema::{ConfigFile, Entry, EntryContent, Operation};
-    use std::collections::HashMap;
+    use crate::schema::{ConfigFile, Entry, EntryContent, Operation, VersionSpec};
+    use std::collections::BTreeMap;
 
-    let mut entries = HashMap::new();
+    let mut entries = BTreeMap::new();
     entries.insert(ENTRY_AUTOTILE.to_string(), VALUE_TRUE.to_string());
     entries.insert(
         ENTRY_AUTOTILE_BEHAVIOR.to_string(),
@@ -1217,14 +1306,16 @@ fn test_apply_command_ron() {
 
     let config = ConfigFile {
         schema: None,
+        metadata: None,
         operations: vec![Entry {
             component: Some(COSMIC_COMP.to_string()),
             file: None,
             value: None,
-            version: Some(VERSION_1),
+            version: Some(VersionSpec::Number(VERSION_1)),
             operation: Operation::Write,
             xdg_directory: Some(XDG_CONFIG_DIR.to_string()),
             entries: Some(EntryContent::WriteEntries(entries)),
+            encoding: None,
         }],
     };
 
@@ -1236,6 +1327,8 @@ fn test_apply_command_ron() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .arg(APPLY_OPERATION)
         .arg(config_file)
@@ -1274,6 +1367,8 @@ fn test_backup_command_toml() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -1292,6 +1387,8 @@ fn test_backup_command_toml() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .arg(BACKUP_OPERATION)
         .arg(&backup_file)
@@ -1315,6 +1412,8 @@ fn test_backup_command_ron() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -1333,6 +1432,8 @@ fn test_backup_command_ron() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .arg(BACKUP_OPERATION)
         .arg(&backup_file)
@@ -1357,6 +1458,8 @@ fn test_backup_command_with_explicit_format() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([
             WRITE_OPERATION,
@@ -1375,6 +1478,8 @@ fn test_backup_command_with_explicit_format() {
 
     Command::cargo_bin("cosmic-ctl")
         .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
         .env("XDG_CONFIG_HOME", config_home)
         .args([BACKUP_OPERATION, "--format", "json"])
         .arg(&backup_file)
@@ -1390,3 +1495,3369 @@ fn test_backup_command_with_explicit_format() {
     assert!(json_data.get("operations").is_some());
     assert!(json_data.get("$schema").is_some());
 }
+
+#[test]
+fn test_backup_command_non_default() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().to_str().unwrap();
+    let data_dir = TempDir::new().unwrap();
+
+    let default_path = data_dir
+        .path()
+        .join("cosmic")
+        .join(COSMIC_COMP)
+        .join(format!("v{}", VERSION_1));
+    fs::create_dir_all(&default_path).unwrap();
+    fs::write(
+        default_path.join(ENTRY_AUTOTILE),
+        format!("{}\n", VALUE_TRUE),
+    )
+    .unwrap();
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .env("XDG_CONFIG_HOME", config_home)
+        .args([
+            WRITE_OPERATION,
+            "--version",
+            &VERSION_1.to_string(),
+            "--component",
+            COSMIC_COMP,
+            "--entry",
+            ENTRY_AUTOTILE,
+            VALUE_TRUE,
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .env("XDG_CONFIG_HOME", config_home)
+        .args([
+            WRITE_OPERATION,
+            "--version",
+            &VERSION_1.to_string(),
+            "--component",
+            COSMIC_COMP,
+            "--entry",
+            ENTRY_AUTOTILE_BEHAVIOR,
+            VALUE_PER_WORKSPACE,
+        ])
+        .assert()
+        .success();
+
+    let backup_file = temp_dir.path().join("backup.json");
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .env("XDG_CONFIG_HOME", config_home)
+        .env("XDG_DATA_DIRS", data_dir.path())
+        .args([BACKUP_OPERATION, "--non-default"])
+        .arg(&backup_file)
+        .assert()
+        .success()
+        .stdout("Backup completed successfully. 1 total entries backed up in JSON format.\nSkipped 1 entries: 1 matching system defaults.\n");
+
+    let backup_content: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(backup_file).unwrap()).unwrap();
+    let entries = &backup_content["operations"][0]["entries"];
+
+    assert!(entries.get(ENTRY_AUTOTILE).is_none());
+    assert_eq!(entries[ENTRY_AUTOTILE_BEHAVIOR], VALUE_PER_WORKSPACE);
+}
+
+#[test]
+fn test_backup_command_non_default_report() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().to_str().unwrap();
+    let data_dir = TempDir::new().unwrap();
+
+    let default_path = data_dir
+        .path()
+        .join("cosmic")
+        .join(COSMIC_COMP)
+        .join(format!("v{}", VERSION_1))
+        .join(ENTRY_AUTOTILE);
+    fs::create_dir_all(default_path.parent().unwrap()).unwrap();
+    fs::write(&default_path, "false").unwrap();
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .env("XDG_CONFIG_HOME", config_home)
+        .args([
+            WRITE_OPERATION,
+            "--version",
+            &VERSION_1.to_string(),
+            "--component",
+            COSMIC_COMP,
+            "--entry",
+            ENTRY_AUTOTILE,
+            VALUE_TRUE,
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .env("XDG_CONFIG_HOME", config_home)
+        .env("XDG_DATA_DIRS", data_dir.path())
+        .args([
+            BACKUP_OPERATION,
+            "--non-default",
+            "--report",
+            "--xdg-dirs",
+            XDG_CONFIG_DIR,
+        ])
+        .assert()
+        .success()
+        .stdout(format!(
+            "[{}] {}/v{}/{} (default: {})\nFound 1 customised entries.\n",
+            XDG_CONFIG_DIR,
+            COSMIC_COMP,
+            VERSION_1,
+            ENTRY_AUTOTILE,
+            default_path.display()
+        ));
+}
+
+#[test]
+fn test_backup_command_with_include_and_exclude() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().to_str().unwrap();
+
+    for (version, entry, value) in [
+        (VERSION_1, ENTRY_AUTOTILE, VALUE_TRUE),
+        (VERSION_1, ENTRY_AUTOTILE_BEHAVIOR, VALUE_PER_WORKSPACE),
+        (VERSION_2, ENTRY_AUTOTILE, VALUE_TRUE),
+    ] {
+        Command::cargo_bin("cosmic-ctl")
+            .unwrap()
+            .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+            .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+            .env("XDG_CONFIG_HOME", config_home)
+            .args([
+                WRITE_OPERATION,
+                "--version",
+                &version.to_string(),
+                "--component",
+                COSMIC_COMP,
+                "--entry",
+                entry,
+                value,
+            ])
+            .assert()
+            .success();
+    }
+
+    let backup_file = temp_dir.path().join("backup.json");
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .env("XDG_CONFIG_HOME", config_home)
+        .args([
+            BACKUP_OPERATION,
+            "--include",
+            &format!("{}/v{}", COSMIC_COMP, VERSION_1),
+            "--exclude",
+            &format!("{}/v{}/{}", COSMIC_COMP, VERSION_1, ENTRY_AUTOTILE_BEHAVIOR),
+        ])
+        .arg(&backup_file)
+        .assert()
+        .success()
+        .stdout("Backup completed successfully. 1 total entries backed up in JSON format.\nSkipped 2 entries: 2 excluded.\n");
+
+    let backup_content: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(backup_file).unwrap()).unwrap();
+    let operations = backup_content["operations"].as_array().unwrap();
+
+    assert_eq!(operations.len(), 1);
+    assert_eq!(operations[0]["version"], VERSION_1);
+    assert_eq!(operations[0]["entries"][ENTRY_AUTOTILE], VALUE_TRUE);
+}
+
+#[test]
+fn test_backup_command_with_invalid_pattern() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().to_str().unwrap();
+    let backup_file = temp_dir.path().join("backup.json");
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .env("XDG_CONFIG_HOME", config_home)
+        .args([BACKUP_OPERATION, "--exclude", "com.system76.[Cosmic"])
+        .arg(&backup_file)
+        .assert()
+        .failure();
+
+    assert!(!backup_file.exists());
+}
+
+#[test]
+fn test_reset_command_with_only_and_exclude() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().to_str().unwrap();
+    const COSMIC_PANEL: &str = "com.system76.CosmicPanel";
+
+    for (component, entry, value) in [
+        (COSMIC_COMP, ENTRY_AUTOTILE, VALUE_TRUE),
+        (COSMIC_COMP, ENTRY_AUTOTILE_BEHAVIOR, VALUE_PER_WORKSPACE),
+        (COSMIC_PANEL, ENTRY_AUTOTILE, VALUE_TRUE),
+    ] {
+        Command::cargo_bin("cosmic-ctl")
+            .unwrap()
+            .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+            .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+            .env("XDG_CONFIG_HOME", config_home)
+            .args([
+                WRITE_OPERATION,
+                "--component",
+                component,
+                "--entry",
+                entry,
+                value,
+            ])
+            .assert()
+            .success();
+    }
+
+    let component_path = |component: &str, entry: &str| {
+        temp_dir
+            .path()
+            .join("cosmic")
+            .join(component)
+            .join(format!("v{}", VERSION_1))
+            .join(entry)
+    };
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .env("XDG_CONFIG_HOME", config_home)
+        .args([
+            "reset",
+            "--force",
+            "--xdg-dirs",
+            XDG_CONFIG_DIR,
+            "--only",
+            "com.system76.Cosmic{Comp,Panel}*",
+            "--exclude",
+            &format!("{}/v{}/{}", COSMIC_COMP, VERSION_1, ENTRY_AUTOTILE),
+        ])
+        .assert()
+        .success()
+        .stdout("Successfully deleted 2 configuration entries.\n");
+
+    assert!(component_path(COSMIC_COMP, ENTRY_AUTOTILE).exists());
+    assert!(!component_path(COSMIC_COMP, ENTRY_AUTOTILE_BEHAVIOR).exists());
+    assert!(!component_path(COSMIC_PANEL, ENTRY_AUTOTILE).exists());
+}
+
+#[test]
+fn test_reset_command_with_component_and_version() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().to_str().unwrap();
+
+    for version in [VERSION_1, VERSION_2] {
+        Command::cargo_bin("cosmic-ctl")
+            .unwrap()
+            .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+            .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+            .env("XDG_CONFIG_HOME", config_home)
+            .args([
+                WRITE_OPERATION,
+                "--version",
+                &version.to_string(),
+                "--component",
+                COSMIC_COMP,
+                "--entry",
+                ENTRY_AUTOTILE,
+                VALUE_TRUE,
+            ])
+            .assert()
+            .success();
+    }
+
+    let autotile_path = |version: u64| {
+        temp_dir
+            .path()
+            .join("cosmic")
+            .join(COSMIC_COMP)
+            .join(format!("v{}", version))
+            .join(ENTRY_AUTOTILE)
+    };
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .env("XDG_CONFIG_HOME", config_home)
+        .args([
+            "reset",
+            "-x",
+            XDG_CONFIG_DIR,
+            "-c",
+            COSMIC_COMP,
+            "--version",
+            &VERSION_2.to_string(),
+        ])
+        .write_stdin("n\n")
+        .assert()
+        .success()
+        .stdout(format!(
+            "Are you sure you want to delete 1 configuration entries from 1 components in XDG directories {}? This action cannot be undone. [y/N] Operation cancelled.\n",
+            XDG_CONFIG_DIR
+        ));
+
+    assert!(autotile_path(VERSION_2).exists());
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .env("XDG_CONFIG_HOME", config_home)
+        .args([
+            "reset",
+            "-x",
+            XDG_CONFIG_DIR,
+            "-c",
+            COSMIC_COMP,
+            "--version",
+            &VERSION_2.to_string(),
+        ])
+        .write_stdin("y\n")
+        .assert()
+        .success();
+
+    assert!(autotile_path(VERSION_1).exists());
+    assert!(!autotile_path(VERSION_2).exists());
+}
+
+#[test]
+fn test_root_and_home_overrides() {
+    let root_dir = TempDir::new().unwrap();
+    let root = root_dir.path().to_str().unwrap();
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", root_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", root_dir.path().join("state"))
+        .args([
+            "--root",
+            root,
+            "--home",
+            "/etc/skel",
+            WRITE_OPERATION,
+            "--component",
+            COSMIC_COMP,
+            "--entry",
+            ENTRY_AUTOTILE,
+            VALUE_TRUE,
+        ])
+        .assert()
+        .success()
+        .stdout("Configuration entry written successfully.\n");
+
+    let config_path = root_dir
+        .path()
+        .join("etc/skel/.config/cosmic")
+        .join(COSMIC_COMP)
+        .join(format!("v{}", VERSION_1))
+        .join(ENTRY_AUTOTILE);
+
+    assert_eq!(fs::read_to_string(config_path).unwrap(), VALUE_TRUE);
+    // The skeleton directory only gets the configuration, not a journal or lock file.
+    assert!(!root_dir
+        .path()
+        .join("etc/skel/.local/state/cosmic-ctl")
+        .exists());
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", root_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", root_dir.path().join("state"))
+        .args([
+            READ_OPERATION,
+            "--root",
+            root,
+            "--home",
+            "/etc/skel",
+            "--component",
+            COSMIC_COMP,
+            "--entry",
+            ENTRY_AUTOTILE,
+        ])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", VALUE_TRUE));
+
+    let backup_file = root_dir.path().join("backup.json");
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", root_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", root_dir.path().join("state"))
+        .args(["--root", root, "--home", "/etc/skel", BACKUP_OPERATION])
+        .arg(&backup_file)
+        .assert()
+        .success()
+        .stdout("Backup completed successfully. 1 total entries backed up in JSON format.\n");
+}
+
+#[test]
+fn test_apply_command_file_confined_to_root() {
+    let root_dir = TempDir::new().unwrap();
+    let root = root_dir.path().to_str().unwrap();
+
+    let config_json = json!({
+        "operations": [
+            {
+                "file": "/etc/cosmic-ctl-test.conf",
+                "operation": WRITE_OPERATION,
+                "value": VALUE_TRUE
+            },
+            {
+                "file": "/../../cosmic-ctl-escape.conf",
+                "operation": WRITE_OPERATION,
+                "value": VALUE_TRUE
+            }
+        ]
+    });
+
+    let config_file = root_dir.path().join("config.json");
+    fs::write(
+        &config_file,
+        serde_json::to_string_pretty(&config_json).unwrap(),
+    )
+    .unwrap();
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", root_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", root_dir.path().join("state"))
+        .args(["--root", root, "--home", "/home/test", APPLY_OPERATION])
+        .arg(&config_file)
+        .arg("--allow-any-path")
+        .assert()
+        .success()
+        .stdout(
+            "Operations completed successfully. 1 writes, 0 reads, 0 deletes, 1 entries skipped.\nSkipped 1 entries: 1 not permitted.\n",
+        );
+
+    assert_eq!(
+        fs::read_to_string(root_dir.path().join("etc/cosmic-ctl-test.conf")).unwrap(),
+        VALUE_TRUE
+    );
+    assert!(!root_dir
+        .path()
+        .parent()
+        .unwrap()
+        .join("cosmic-ctl-escape.conf")
+        .exists());
+}
+
+#[test]
+fn test_user_override_sets_ownership() {
+    use std::os::unix::fs::MetadataExt;
+
+    let root_dir = TempDir::new().unwrap();
+    let root = root_dir.path().to_str().unwrap();
+    let owner = fs::metadata(root_dir.path()).unwrap();
+
+    fs::create_dir_all(root_dir.path().join("etc")).unwrap();
+    fs::write(
+        root_dir.path().join("etc/passwd"),
+        format!(
+            "root:x:0:0:root:/root:/bin/sh\ntester:x:{}:{}:Tester:/home/tester:/bin/sh\n",
+            owner.uid(),
+            owner.gid()
+        ),
+    )
+    .unwrap();
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", root_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", root_dir.path().join("state"))
+        .args([
+            "--root",
+            root,
+            "--user",
+            "tester",
+            WRITE_OPERATION,
+            "--component",
+            COSMIC_COMP,
+            "--entry",
+            ENTRY_AUTOTILE,
+            VALUE_TRUE,
+        ])
+        .assert()
+        .success()
+        .stdout("Configuration entry written successfully.\n");
+
+    let config_dir = root_dir.path().join("home/tester/.config");
+    let config_path = config_dir
+        .join("cosmic")
+        .join(COSMIC_COMP)
+        .join(format!("v{}", VERSION_1))
+        .join(ENTRY_AUTOTILE);
+
+    assert_eq!(fs::read_to_string(&config_path).unwrap(), VALUE_TRUE);
+
+    for path in [config_dir.as_path(), config_path.as_path()] {
+        let metadata = fs::metadata(path).unwrap();
+        assert_eq!(metadata.uid(), owner.uid());
+        assert_eq!(metadata.gid(), owner.gid());
+    }
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", root_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", root_dir.path().join("state"))
+        .args([
+            "--root",
+            root,
+            "--user",
+            "nobody-here",
+            READ_OPERATION,
+            "--component",
+            COSMIC_COMP,
+            "--entry",
+            ENTRY_AUTOTILE,
+        ])
+        .assert()
+        .failure()
+        .stderr("Error: Unknown user: nobody-here\n");
+}
+
+#[test]
+fn test_backup_metadata_roundtrip() {
+    for extension in ["json", "toml", "ron"] {
+        let temp_dir = TempDir::new().unwrap();
+        let config_home = temp_dir.path().to_str().unwrap();
+
+        Command::cargo_bin("cosmic-ctl")
+            .unwrap()
+            .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+            .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+            .env("XDG_CONFIG_HOME", config_home)
+            .args([
+                WRITE_OPERATION,
+                "--component",
+                COSMIC_COMP,
+                "--entry",
+                ENTRY_XKB_CONFIG,
+                VALUE_XKB_CONFIG,
+            ])
+            .assert()
+            .success();
+
+        let backup_file = temp_dir.path().join(format!("backup.{}", extension));
+
+        Command::cargo_bin("cosmic-ctl")
+            .unwrap()
+            .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+            .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+            .env("XDG_CONFIG_HOME", config_home)
+            .args([BACKUP_OPERATION, "--xdg-dirs", XDG_CONFIG_DIR])
+            .arg(&backup_file)
+            .assert()
+            .success();
+
+        let backup_content = fs::read_to_string(&backup_file).unwrap();
+        assert!(backup_content.contains("sha256:"));
+        assert!(backup_content.contains(env!("CARGO_PKG_VERSION")));
+
+        Command::cargo_bin("cosmic-ctl")
+            .unwrap()
+            .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+            .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+            .env("XDG_CONFIG_HOME", config_home)
+            .arg(APPLY_OPERATION)
+            .arg(&backup_file)
+            .assert()
+            .success()
+            .stdout(
+                "Operations completed successfully. 0 writes, 0 reads, 0 deletes, 1 entries skipped.\nSkipped 1 entries: 1 unchanged.\n",
+            );
+    }
+}
+
+#[test]
+fn test_apply_command_checksum_mismatch() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().to_str().unwrap();
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .env("XDG_CONFIG_HOME", config_home)
+        .args([
+            WRITE_OPERATION,
+            "--component",
+            COSMIC_COMP,
+            "--entry",
+            ENTRY_AUTOTILE,
+            VALUE_TRUE,
+        ])
+        .assert()
+        .success();
+
+    let backup_file = temp_dir.path().join("backup.json");
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .env("XDG_CONFIG_HOME", config_home)
+        .args([BACKUP_OPERATION, "--xdg-dirs", XDG_CONFIG_DIR])
+        .arg(&backup_file)
+        .assert()
+        .success();
+
+    let mut backup_content: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&backup_file).unwrap()).unwrap();
+    backup_content["operations"][0]["entries"][ENTRY_AUTOTILE] = json!("false");
+    backup_content["metadata"]["hostname"] = json!("another-host");
+    fs::write(
+        &backup_file,
+        serde_json::to_string_pretty(&backup_content).unwrap(),
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .env("XDG_CONFIG_HOME", config_home)
+        .arg(APPLY_OPERATION)
+        .arg(&backup_file)
+        .assert()
+        .failure()
+        .get_output()
+        .clone();
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Checksum mismatch"));
+
+    let output = Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .env("XDG_CONFIG_HOME", config_home)
+        .args([APPLY_OPERATION, "--ignore-checksums"])
+        .arg(&backup_file)
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Warning: backup was created on host 'another-host'"));
+}
+
+#[test]
+fn test_log_command() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().join("config");
+    let state_home = temp_dir.path().join("state");
+
+    for value in [VALUE_TRUE, "false"] {
+        Command::cargo_bin("cosmic-ctl")
+            .unwrap()
+            .env("XDG_CONFIG_HOME", &config_home)
+            .env("XDG_STATE_HOME", &state_home)
+            .env("XDG_RUNTIME_DIR", &state_home)
+            .args([
+                WRITE_OPERATION,
+                "--component",
+                COSMIC_COMP,
+                "--entry",
+                ENTRY_AUTOTILE,
+                value,
+            ])
+            .assert()
+            .success();
+    }
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", &config_home)
+        .env("XDG_STATE_HOME", &state_home)
+        .env("XDG_RUNTIME_DIR", &state_home)
+        .args([
+            DELETE_OPERATION,
+            "--component",
+            COSMIC_COMP,
+            "--entry",
+            ENTRY_AUTOTILE,
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", &config_home)
+        .env("XDG_STATE_HOME", &state_home)
+        .env("XDG_RUNTIME_DIR", &state_home)
+        .args([
+            "--no-journal",
+            WRITE_OPERATION,
+            "--component",
+            "com.system76.CosmicPanel",
+            "--entry",
+            ENTRY_AUTOTILE,
+            VALUE_TRUE,
+        ])
+        .assert()
+        .success();
+
+    let output = Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", &state_home)
+        .env("XDG_RUNTIME_DIR", &state_home)
+        .args([
+            "log",
+            "--component",
+            "com.system76.Cosmic*",
+            "--since",
+            "1h",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    let address = format!(
+        "[{}] {}/v{}/{}",
+        XDG_CONFIG_DIR, COSMIC_COMP, VERSION_1, ENTRY_AUTOTILE
+    );
+
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].ends_with(&format!("write {}: (none) -> \"true\"", address)));
+    assert!(lines[1].ends_with(&format!("write {}: \"true\" -> \"false\"", address)));
+    assert!(lines[2].ends_with(&format!("delete {}: \"false\" -> (none)", address)));
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", &state_home)
+        .env("XDG_RUNTIME_DIR", &state_home)
+        .args(["log", "--since", "2999-01-01"])
+        .assert()
+        .success()
+        .stdout("");
+}
+
+#[test]
+fn test_journal_rotation() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().join("config");
+    let state_home = temp_dir.path().join("state");
+
+    for value in ["1", "2", "3", "4"] {
+        Command::cargo_bin("cosmic-ctl")
+            .unwrap()
+            .env("XDG_CONFIG_HOME", &config_home)
+            .env("XDG_STATE_HOME", &state_home)
+            .env("XDG_RUNTIME_DIR", &state_home)
+            .args([
+                "--journal-max-size",
+                "1",
+                "--journal-max-files",
+                "2",
+                WRITE_OPERATION,
+                "--component",
+                COSMIC_COMP,
+                "--entry",
+                ENTRY_AUTOTILE,
+                value,
+            ])
+            .assert()
+            .success();
+    }
+
+    let journal_dir = state_home.join("cosmic-ctl");
+    assert!(journal_dir.join("journal.jsonl").exists());
+    assert!(journal_dir.join("journal.jsonl.1").exists());
+    assert!(journal_dir.join("journal.jsonl.2").exists());
+    assert!(!journal_dir.join("journal.jsonl.3").exists());
+
+    let output = Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", &state_home)
+        .env("XDG_RUNTIME_DIR", &state_home)
+        .arg("log")
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 3);
+    assert!(stdout.lines().last().unwrap().ends_with("\"3\" -> \"4\""));
+}
+
+fn create_editor_script(dir: &std::path::Path, body: &str) -> std::path::PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let script = dir.join("editor.sh");
+    fs::write(&script, format!("#!/bin/sh\n{}\n", body)).unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+    script
+}
+
+#[test]
+fn test_edit_command() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().join("config");
+    let value_file = temp_dir.path().join("value.ron");
+    fs::write(&value_file, VALUE_XKB_CONFIG).unwrap();
+    let editor = create_editor_script(
+        temp_dir.path(),
+        &format!("cp '{}' \"$1\"", value_file.display()),
+    );
+
+    let output = Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", &config_home)
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .env("VISUAL", &editor)
+        .args([
+            "edit",
+            "--component",
+            COSMIC_COMP,
+            "--entry",
+            ENTRY_XKB_CONFIG,
+        ])
+        .write_stdin("y\n")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("+    layout: \"br\","));
+    assert!(stdout.contains("Configuration entry written successfully."));
+
+    let config_path = config_home
+        .join("cosmic")
+        .join(COSMIC_COMP)
+        .join(format!("v{}", VERSION_1))
+        .join(ENTRY_XKB_CONFIG);
+    assert_eq!(fs::read_to_string(config_path).unwrap(), VALUE_XKB_CONFIG);
+}
+
+#[test]
+fn test_edit_command_invalid_ron_reopens_editor() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().join("config");
+    let attempts = temp_dir.path().join("attempts");
+    let editor = create_editor_script(
+        temp_dir.path(),
+        &format!(
+            "if [ -e '{attempts}' ]; then grep -q '^// cosmic-ctl: Invalid RON' \"$1\" && printf 'true' > \"$1\"; else touch '{attempts}'; printf '(' > \"$1\"; fi",
+            attempts = attempts.display()
+        ),
+    );
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", &config_home)
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .env("VISUAL", &editor)
+        .args([
+            "edit",
+            "--component",
+            COSMIC_COMP,
+            "--entry",
+            ENTRY_AUTOTILE,
+            "--yes",
+        ])
+        .assert()
+        .success();
+
+    let config_path = config_home
+        .join("cosmic")
+        .join(COSMIC_COMP)
+        .join(format!("v{}", VERSION_1))
+        .join(ENTRY_AUTOTILE);
+    assert_eq!(fs::read_to_string(config_path).unwrap(), VALUE_TRUE);
+}
+
+#[test]
+fn test_edit_command_cancelled() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_file = temp_dir.path().join(ENTRY_AUTOTILE);
+    fs::write(&config_file, "false").unwrap();
+    let editor = create_editor_script(temp_dir.path(), "printf 'true' > \"$1\"");
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .env("VISUAL", &editor)
+        .args(["edit", "--file", config_file.to_str().unwrap()])
+        .write_stdin("n\n")
+        .assert()
+        .success();
+
+    assert_eq!(fs::read_to_string(&config_file).unwrap(), "false");
+}
+
+#[test]
+fn test_search_command() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().join("config");
+    let data_dir = temp_dir.path().join("share");
+
+    let write_entry = |cosmic_path: &std::path::Path, component: &str, entry: &str, value: &str| {
+        let version_path = cosmic_path.join(component).join(format!("v{}", VERSION_1));
+        fs::create_dir_all(&version_path).unwrap();
+        fs::write(version_path.join(entry), value).unwrap();
+    };
+    write_entry(
+        &config_home.join("cosmic"),
+        "com.system76.CosmicBackground",
+        "all",
+        "(\n    output: \"all\",\n    source: Path(\"/usr/share/backgrounds/wallpaper.png\"),\n)",
+    );
+    write_entry(
+        &config_home.join("cosmic"),
+        COSMIC_COMP,
+        ENTRY_AUTOTILE,
+        VALUE_TRUE,
+    );
+    write_entry(
+        &data_dir.join("cosmic"),
+        "com.system76.CosmicBackground",
+        "same-on-all",
+        VALUE_TRUE,
+    );
+
+    let search = |args: &[&str]| {
+        let output = Command::cargo_bin("cosmic-ctl")
+            .unwrap()
+            .env("XDG_CONFIG_HOME", &config_home)
+            .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+            .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+            .env("XDG_DATA_DIRS", &data_dir)
+            .arg("search")
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    let stdout = search(&["wallpaper"]);
+    assert_eq!(
+        stdout,
+        "[config] com.system76.CosmicBackground/v1/all\n  3:     source: Path(\"/usr/share/backgrounds/wallpaper.png\"),\n"
+    );
+
+    let stdout = search(&["auto", "--keys-only"]);
+    assert_eq!(stdout, "[config] com.system76.CosmicComp/v1/autotile\n");
+
+    let stdout = search(&["auto", "--values-only"]);
+    assert_eq!(stdout, "No matches found.\n");
+
+    let stdout = search(&[
+        "true",
+        "--component",
+        "com.system76.CosmicBackground",
+        "--defaults",
+    ]);
+    assert_eq!(
+        stdout,
+        "[defaults] com.system76.CosmicBackground/v1/same-on-all\n  1: true\n"
+    );
+
+    let stdout = search(&["autotile", "--output", "json"]);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(
+        json,
+        json!([{
+            "source": XDG_CONFIG_DIR,
+            "address": "com.system76.CosmicComp/v1/autotile",
+            "key_match": true,
+            "lines": [],
+        }])
+    );
+}
+
+#[test]
+fn test_search_command_invalid_regex() {
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .args(["search", "("])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_write_command_preserves_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let config_file = temp_dir.path().join(ENTRY_AUTOTILE);
+    fs::write(&config_file, "false").unwrap();
+    fs::set_permissions(&config_file, fs::Permissions::from_mode(0o600)).unwrap();
+    let _ = xattr::set(&config_file, "user.cosmic-ctl.test", b"kept");
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .args([
+            WRITE_OPERATION,
+            "--file",
+            config_file.to_str().unwrap(),
+            VALUE_TRUE,
+        ])
+        .assert()
+        .success();
+
+    assert_eq!(fs::read_to_string(&config_file).unwrap(), VALUE_TRUE);
+    let mode = fs::metadata(&config_file).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+    if let Ok(Some(value)) = xattr::get(&config_file, "user.cosmic-ctl.test") {
+        assert_eq!(value, b"kept");
+    }
+}
+
+#[test]
+fn test_write_command_read_only_entry() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().join("config");
+    let version_path = config_home
+        .join("cosmic")
+        .join(COSMIC_COMP)
+        .join(format!("v{}", VERSION_1));
+    fs::create_dir_all(&version_path).unwrap();
+    let config_path = version_path.join(ENTRY_AUTOTILE);
+    fs::write(&config_path, "false").unwrap();
+    fs::set_permissions(&config_path, fs::Permissions::from_mode(0o444)).unwrap();
+
+    let write = |force: bool| {
+        let mut command = Command::cargo_bin("cosmic-ctl").unwrap();
+        command
+            .env("XDG_CONFIG_HOME", &config_home)
+            .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+            .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+            .args([
+                WRITE_OPERATION,
+                "--component",
+                COSMIC_COMP,
+                "--entry",
+                ENTRY_AUTOTILE,
+                VALUE_TRUE,
+            ]);
+        if force {
+            command.arg("--force");
+        }
+        command.output().unwrap()
+    };
+
+    let output = write(false);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("is read-only, use --force"));
+    assert_eq!(fs::read_to_string(&config_path).unwrap(), "false");
+
+    assert!(write(true).status.success());
+    assert_eq!(fs::read_to_string(&config_path).unwrap(), VALUE_TRUE);
+    let mode = fs::metadata(&config_path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o444);
+}
+
+fn create_symlinked_entry(temp_dir: &TempDir) -> (std::path::PathBuf, std::path::PathBuf) {
+    let dotfiles_entry = temp_dir.path().join("dotfiles").join(ENTRY_AUTOTILE);
+    fs::create_dir_all(dotfiles_entry.parent().unwrap()).unwrap();
+    fs::write(&dotfiles_entry, "false").unwrap();
+
+    let version_path = temp_dir
+        .path()
+        .join("config")
+        .join("cosmic")
+        .join(COSMIC_COMP)
+        .join(format!("v{}", VERSION_1));
+    fs::create_dir_all(&version_path).unwrap();
+    let config_path = version_path.join(ENTRY_AUTOTILE);
+    std::os::unix::fs::symlink(&dotfiles_entry, &config_path).unwrap();
+
+    (config_path, dotfiles_entry)
+}
+
+#[test]
+fn test_write_command_symlinks() {
+    for replace in [false, true] {
+        let temp_dir = TempDir::new().unwrap();
+        let (config_path, dotfiles_entry) = create_symlinked_entry(&temp_dir);
+
+        let mut command = Command::cargo_bin("cosmic-ctl").unwrap();
+        command
+            .env("XDG_CONFIG_HOME", temp_dir.path().join("config"))
+            .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+            .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+            .args([
+                WRITE_OPERATION,
+                "--component",
+                COSMIC_COMP,
+                "--entry",
+                ENTRY_AUTOTILE,
+                VALUE_TRUE,
+            ]);
+        if replace {
+            command.arg("--replace-symlinks");
+        }
+        command.assert().success();
+
+        let is_symlink = fs::symlink_metadata(&config_path).unwrap().is_symlink();
+        assert_eq!(is_symlink, !replace);
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), VALUE_TRUE);
+        assert_eq!(
+            fs::read_to_string(&dotfiles_entry).unwrap(),
+            if replace { "false" } else { VALUE_TRUE }
+        );
+    }
+}
+
+#[test]
+fn test_backup_and_reset_symlinks() {
+    let temp_dir = TempDir::new().unwrap();
+    let (config_path, dotfiles_entry) = create_symlinked_entry(&temp_dir);
+    let backup_file = temp_dir.path().join("backup.json");
+
+    let output = Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", temp_dir.path().join("config"))
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .args([BACKUP_OPERATION, backup_file.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout)
+        .contains("Backed up the target values of 1 symlinked entries"));
+    let backup: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&backup_file).unwrap()).unwrap();
+    assert_eq!(
+        backup["operations"][0]["entries"][ENTRY_AUTOTILE],
+        json!("false")
+    );
+
+    let output = Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", temp_dir.path().join("config"))
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .args(["reset", "--force", "--xdg-dirs", XDG_CONFIG_DIR])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Removing symlink [config]"));
+    assert!(fs::symlink_metadata(&config_path).is_err());
+    assert_eq!(fs::read_to_string(&dotfiles_entry).unwrap(), "false");
+}
+
+#[test]
+fn test_hostile_addresses_rejected() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().join("config");
+    let escaped_file = temp_dir.path().join(".bashrc");
+
+    let hostile_addresses = [
+        ("..", ENTRY_AUTOTILE),
+        ("com.system76.CosmicComp/../..", ENTRY_AUTOTILE),
+        ("/tmp", ENTRY_AUTOTILE),
+        ("CosmicComp", ENTRY_AUTOTILE),
+        ("com..system76", ENTRY_AUTOTILE),
+        (COSMIC_COMP, "../../../../.bashrc"),
+        (COSMIC_COMP, ".."),
+        (COSMIC_COMP, ".hidden"),
+        (COSMIC_COMP, "output.HDMI-A-1"),
+        (COSMIC_COMP, "a/b"),
+        (COSMIC_COMP, ""),
+    ];
+
+    for (component, entry) in hostile_addresses {
+        for operation in [WRITE_OPERATION, READ_OPERATION, DELETE_OPERATION] {
+            let mut command = Command::cargo_bin("cosmic-ctl").unwrap();
+            command
+                .env("XDG_CONFIG_HOME", &config_home)
+                .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+                .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+                .args([operation, "--component", component, "--entry", entry]);
+            if operation == WRITE_OPERATION {
+                command.arg(VALUE_TRUE);
+            }
+
+            let output = command.output().unwrap();
+            assert!(
+                !output.status.success(),
+                "{} accepted {}/{}",
+                operation,
+                component,
+                entry
+            );
+            assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid"));
+        }
+    }
+
+    assert!(!escaped_file.exists());
+    assert!(!config_home.join("cosmic").exists());
+}
+
+#[test]
+fn test_apply_command_hostile_entries() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().join("config");
+    let apply_file = temp_dir.path().join("apply.json");
+
+    fs::write(
+        &apply_file,
+        json!({
+            "operations": [
+                {
+                    "component": COSMIC_COMP,
+                    "version": VERSION_1,
+                    "operation": WRITE_OPERATION,
+                    "xdg_directory": XDG_CONFIG_DIR,
+                    "entries": {
+                        "../../../escaped": VALUE_TRUE,
+                        ENTRY_AUTOTILE: VALUE_TRUE,
+                    }
+                },
+                {
+                    "component": "../..",
+                    "version": VERSION_1,
+                    "operation": WRITE_OPERATION,
+                    "xdg_directory": XDG_CONFIG_DIR,
+                    "entries": { ENTRY_AUTOTILE: VALUE_TRUE }
+                }
+            ]
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", &config_home)
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .args([APPLY_OPERATION, apply_file.to_str().unwrap()])
+        .output()
+        .unwrap();
+
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid entry name"));
+    assert!(!temp_dir.path().join("escaped").exists());
+    assert!(!config_home.join("escaped").exists());
+    assert!(!config_home.join(ENTRY_AUTOTILE).exists());
+    assert!(!temp_dir.path().join("v1").exists());
+    assert_eq!(
+        fs::read_to_string(
+            config_home
+                .join("cosmic")
+                .join(COSMIC_COMP)
+                .join(format!("v{}", VERSION_1))
+                .join(ENTRY_AUTOTILE)
+        )
+        .unwrap(),
+        VALUE_TRUE
+    );
+}
+
+#[test]
+fn test_apply_command_file_allow_list() {
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path().join("home");
+    let outside_file = temp_dir.path().join("outside").join("ssh-config");
+    fs::create_dir_all(outside_file.parent().unwrap()).unwrap();
+    fs::write(&outside_file, "Host *").unwrap();
+
+    let config_file = temp_dir.path().join("config.json");
+    fs::write(
+        &config_file,
+        json!({
+            "operations": [
+                {
+                    "file": "~/.config/cosmic-ctl-test.conf",
+                    "operation": WRITE_OPERATION,
+                    "value": VALUE_TRUE
+                },
+                {
+                    "file": outside_file,
+                    "operation": DELETE_OPERATION
+                }
+            ]
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    let apply = |extra_args: &[&str]| {
+        Command::cargo_bin("cosmic-ctl")
+            .unwrap()
+            .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+            .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+            .args(["--home", home.to_str().unwrap(), APPLY_OPERATION])
+            .arg(&config_file)
+            .args(extra_args)
+            .output()
+            .unwrap()
+    };
+
+    let output = apply(&[]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("ssh-config is outside the allowed directories"));
+    assert!(stderr.contains("1 file operations are outside"));
+    assert!(outside_file.exists());
+    assert!(!home.join(".config").join("cosmic-ctl-test.conf").exists());
+
+    let outside_dir = temp_dir.path().join("outside");
+    let output = apply(&["--allow-path", outside_dir.to_str().unwrap()]);
+    assert!(output.status.success());
+    assert!(!outside_file.exists());
+    assert_eq!(
+        fs::read_to_string(home.join(".config").join("cosmic-ctl-test.conf")).unwrap(),
+        VALUE_TRUE
+    );
+}
+
+#[test]
+fn test_binary_entry_backup_and_apply() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().join("config");
+    let version_path = config_home
+        .join("cosmic")
+        .join(COSMIC_COMP)
+        .join(format!("v{}", VERSION_1));
+    fs::create_dir_all(&version_path).unwrap();
+    let binary_value: &[u8] = &[0x00, 0xff, 0xfe, b'\\', b'n', 0x80];
+    fs::write(version_path.join("binary"), binary_value).unwrap();
+    fs::write(version_path.join(ENTRY_AUTOTILE), VALUE_TRUE).unwrap();
+    let backup_file = temp_dir.path().join("backup.json");
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", &config_home)
+        .args([BACKUP_OPERATION, "--xdg-dirs", XDG_CONFIG_DIR])
+        .arg(&backup_file)
+        .assert()
+        .success()
+        .stdout("Backup completed successfully. 2 total entries backed up in JSON format.\n");
+
+    let backup: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&backup_file).unwrap()).unwrap();
+    let binary_operation = backup["operations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|operation| operation["encoding"] == "base64")
+        .unwrap();
+    assert_eq!(binary_operation["entries"]["binary"], json!("AP/+XG6A"));
+
+    let output = Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", &config_home)
+        .args([
+            READ_OPERATION,
+            "--component",
+            COSMIC_COMP,
+            "--entry",
+            "binary",
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("use --raw"));
+
+    fs::remove_dir_all(&config_home).unwrap();
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", &config_home)
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .arg(APPLY_OPERATION)
+        .arg(&backup_file)
+        .assert()
+        .success();
+    assert_eq!(fs::read(version_path.join("binary")).unwrap(), binary_value);
+
+    let output = Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", &config_home)
+        .args([
+            READ_OPERATION,
+            "--component",
+            COSMIC_COMP,
+            "--entry",
+            "binary",
+            "--raw",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, binary_value);
+}
+
+#[test]
+fn test_lock_blocks_concurrent_changes() {
+    use std::process::Stdio;
+
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().join("config");
+    let runtime_dir = temp_dir.path().join("runtime");
+    let state_home = temp_dir.path().join("state");
+
+    let cosmic_ctl = || {
+        let mut command = std::process::Command::new(assert_cmd::cargo::cargo_bin("cosmic-ctl"));
+        command
+            .env("XDG_CONFIG_HOME", &config_home)
+            .env("XDG_RUNTIME_DIR", &runtime_dir)
+            .env("XDG_STATE_HOME", &state_home);
+        command
+    };
+    let write_autotile = |value: &str, extra_args: &[&str]| {
+        cosmic_ctl()
+            .args([
+                WRITE_OPERATION,
+                "--component",
+                COSMIC_COMP,
+                "--entry",
+                ENTRY_AUTOTILE,
+                value,
+            ])
+            .args(extra_args)
+            .output()
+            .unwrap()
+    };
+
+    assert!(write_autotile("false", &[]).status.success());
+
+    // Another process holding the lock, like a long-running apply.
+    let lock_dir = runtime_dir.join("cosmic-ctl");
+    fs::create_dir_all(&lock_dir).unwrap();
+    let lock_file = fs::File::create(lock_dir.join("lock")).unwrap();
+    // SAFETY: the descriptor belongs to `lock_file`, which stays open for the call.
+    let locked = unsafe {
+        libc::flock(
+            std::os::unix::io::AsRawFd::as_raw_fd(&lock_file),
+            libc::LOCK_EX,
+        )
+    };
+    assert_eq!(locked, 0);
+
+    let output = write_autotile(VALUE_TRUE, &[]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("use --wait"));
+
+    let output = write_autotile(VALUE_TRUE, &["--timeout", "200ms"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Timed out after 200ms"));
+
+    let waiting_write = cosmic_ctl()
+        .args([
+            WRITE_OPERATION,
+            "--component",
+            COSMIC_COMP,
+            "--entry",
+            ENTRY_AUTOTILE,
+            VALUE_TRUE,
+            "--wait",
+        ])
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    drop(lock_file);
+    assert!(waiting_write.wait_with_output().unwrap().status.success());
+
+    let config_path = config_home
+        .join("cosmic")
+        .join(COSMIC_COMP)
+        .join(format!("v{}", VERSION_1))
+        .join(ENTRY_AUTOTILE);
+    assert_eq!(fs::read_to_string(config_path).unwrap(), VALUE_TRUE);
+}
+
+#[test]
+fn test_lock_serialises_concurrent_applies() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().join("config");
+
+    let apply_files: Vec<_> = ["first", "second"]
+        .iter()
+        .map(|name| {
+            let entries: serde_json::Map<String, serde_json::Value> = (0..50)
+                .map(|index| (format!("{}_{}", name, index), json!(index.to_string())))
+                .collect();
+            let apply_file = temp_dir.path().join(format!("{}.json", name));
+            fs::write(
+                &apply_file,
+                json!({
+                    "operations": [{
+                        "component": COSMIC_COMP,
+                        "version": VERSION_1,
+                        "operation": WRITE_OPERATION,
+                        "xdg_directory": XDG_CONFIG_DIR,
+                        "entries": entries
+                    }]
+                })
+                .to_string(),
+            )
+            .unwrap();
+            apply_file
+        })
+        .collect();
+
+    let applies: Vec<_> = apply_files
+        .iter()
+        .map(|apply_file| {
+            std::process::Command::new(assert_cmd::cargo::cargo_bin("cosmic-ctl"))
+                .env("XDG_CONFIG_HOME", &config_home)
+                .env("XDG_RUNTIME_DIR", temp_dir.path().join("runtime"))
+                .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+                .args(["--wait", APPLY_OPERATION])
+                .arg(apply_file)
+                .stdout(std::process::Stdio::piped())
+                .spawn()
+                .unwrap()
+        })
+        .collect();
+
+    for apply in applies {
+        let output = apply.wait_with_output().unwrap();
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "Operations completed successfully. 50 writes, 0 reads, 0 deletes, 0 entries skipped.\n"
+        );
+    }
+
+    let journal = fs::read_to_string(
+        temp_dir
+            .path()
+            .join("state")
+            .join("cosmic-ctl")
+            .join("journal.jsonl"),
+    )
+    .unwrap();
+    let commands: Vec<String> = journal
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["address"].to_string())
+        .collect();
+    assert_eq!(commands.len(), 100);
+    // One apply runs to completion before the other starts.
+    let first_prefix = commands[0].contains("first_");
+    assert!(commands[..50]
+        .iter()
+        .all(|address| address.contains("first_") == first_prefix));
+}
+
+#[test]
+fn test_backup_output_is_deterministic() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().join("config");
+
+    for component in ["com.example.B", "com.example.A"] {
+        for version in [2, 1] {
+            let dir = config_home
+                .join("cosmic")
+                .join(component)
+                .join(format!("v{}", version));
+            fs::create_dir_all(&dir).unwrap();
+            for entry in ["zeta", "alpha", "mid"] {
+                fs::write(dir.join(entry), format!("\"{}\"", entry)).unwrap();
+            }
+        }
+    }
+
+    let backup = |name: &str| {
+        let backup_file = temp_dir.path().join(name);
+        Command::cargo_bin("cosmic-ctl")
+            .unwrap()
+            .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+            .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+            .env("XDG_CONFIG_HOME", &config_home)
+            .args([BACKUP_OPERATION, "--xdg-dirs", XDG_CONFIG_DIR])
+            .arg(&backup_file)
+            .assert()
+            .success();
+        let backup: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(backup_file).unwrap()).unwrap();
+        backup
+    };
+
+    let first = backup("first.json");
+    let second = backup("second.json");
+    assert_eq!(first["operations"], second["operations"]);
+    assert_eq!(
+        first["metadata"]["checksum"],
+        second["metadata"]["checksum"]
+    );
+
+    let addresses: Vec<String> = first["operations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|operation| format!("{}/v{}", operation["component"], operation["version"]))
+        .collect();
+    assert_eq!(
+        addresses,
+        [
+            "\"com.example.A\"/v1",
+            "\"com.example.A\"/v2",
+            "\"com.example.B\"/v1",
+            "\"com.example.B\"/v2"
+        ]
+    );
+}
+
+#[test]
+fn test_describe_command() {
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .args(["describe", "--component", COSMIC_COMP, "--entry", ENTRY_AUTOTILE_BEHAVIOR])
+        .assert()
+        .success()
+        .stdout(format!(
+            "{}/v1/{}\n  Type: Enum\n  Values: Global, PerWorkspace\n  Default: PerWorkspace\n  Whether autotile applies to all workspaces at once or is toggled per workspace.\n",
+            COSMIC_COMP, ENTRY_AUTOTILE_BEHAVIOR
+        ));
+
+    let output = Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .args(["describe", "--component", COSMIC_COMP])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with(&format!("{}\n  The COSMIC compositor", COSMIC_COMP)));
+    assert!(stdout.contains(
+        "\nv1:\n  autotile (Bool, default: false)\n    Tile new windows automatically.\n"
+    ));
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .args(["describe", "--component", "com.example.Unknown"])
+        .assert()
+        .failure()
+        .stderr("Error: Component 'com.example.Unknown' is not in the catalog\n");
+}
+
+#[test]
+fn test_write_and_apply_check() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().to_str().unwrap();
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .env("XDG_CONFIG_HOME", config_home)
+        .args([
+            WRITE_OPERATION,
+            "--component",
+            COSMIC_COMP,
+            "--entry",
+            ENTRY_AUTOTILE_BEHAVIOR,
+            "Sometimes",
+            "--check",
+        ])
+        .assert()
+        .success()
+        .stdout("Configuration entry written successfully.\n")
+        .stderr(format!(
+            "Warning: {}/v1/{} expects Enum: 'Sometimes' is not one of Global, PerWorkspace\n",
+            COSMIC_COMP, ENTRY_AUTOTILE_BEHAVIOR
+        ));
+
+    // Without --check nothing is reported.
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .env("XDG_CONFIG_HOME", config_home)
+        .args([
+            WRITE_OPERATION,
+            "--component",
+            COSMIC_COMP,
+            "--entry",
+            "autotiles",
+            VALUE_TRUE,
+        ])
+        .assert()
+        .success()
+        .stderr("");
+
+    let apply_file = temp_dir.path().join("apply.json");
+    fs::write(
+        &apply_file,
+        json!({
+            "operations": [{
+                "component": COSMIC_COMP,
+                "version": VERSION_1,
+                "operation": WRITE_OPERATION,
+                "entries": {
+                    ENTRY_AUTOTILE: VALUE_TRUE,
+                    ENTRY_XKB_CONFIG: VALUE_XKB_CONFIG,
+                    "autotiles": VALUE_TRUE,
+                    "focus_follows_cursor_delay": "\"soon\""
+                }
+            }]
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .env("XDG_CONFIG_HOME", config_home)
+        .args([APPLY_OPERATION, "--check"])
+        .arg(&apply_file)
+        .assert()
+        .success()
+        .stderr(format!(
+            "Warning: Unknown entry {0}/v1/autotiles\nWarning: {0}/v1/focus_follows_cursor_delay expects Integer: got '\"soon\"'\n",
+            COSMIC_COMP
+        ));
+}
+
+#[test]
+fn test_migrate_command() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().join("config");
+    let state_home = temp_dir.path().join("state");
+    let component_dir = config_home.join("cosmic").join(COSMIC_COMP);
+
+    let v1 = component_dir.join("v1");
+    fs::create_dir_all(&v1).unwrap();
+    fs::write(v1.join(ENTRY_AUTOTILE), VALUE_TRUE).unwrap();
+    fs::write(v1.join("old_name"), "42").unwrap();
+    fs::write(v1.join("obsolete"), "()").unwrap();
+    fs::write(v1.join("delay"), "250").unwrap();
+    fs::write(v1.join("shared"), "1").unwrap();
+    let v2 = component_dir.join("v2");
+    fs::create_dir_all(&v2).unwrap();
+    fs::write(v2.join("shared"), "2").unwrap();
+
+    let rules_file = temp_dir.path().join("rules.json");
+    fs::write(
+        &rules_file,
+        json!({
+            "rules": [
+                { "rename": { "from": "old_name", "to": "new_name" } },
+                { "drop": { "entry": "obsolete" } },
+                { "wrap": { "entry": "delay", "template": "Some({})" } }
+            ]
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    let migrate = |extra_args: &[&str]| {
+        let mut command = Command::cargo_bin("cosmic-ctl").unwrap();
+        command
+            .env("XDG_CONFIG_HOME", &config_home)
+            .env("XDG_STATE_HOME", &state_home)
+            .env("XDG_RUNTIME_DIR", &state_home)
+            .args([
+                "migrate",
+                "--component",
+                COSMIC_COMP,
+                "--from",
+                "1",
+                "--to",
+                "2",
+            ])
+            .arg("--rules")
+            .arg(&rules_file)
+            .args(extra_args);
+        command
+    };
+
+    migrate(&["--dry-run"])
+        .assert()
+        .success()
+        .stdout(format!(
+            "Would copy {0}/v1/autotile to v2/autotile\n\
+             Would copy {0}/v1/delay to v2/delay\n\
+             Dropping {0}/v1/obsolete\n\
+             Would copy {0}/v1/old_name to v2/new_name\n\
+             1 entries exist in both versions with different values, use --overwrite to replace them:\n  {0}/v2/shared\n\
+             Dry run: 3 entries would be migrated from v1 to v2.\n\
+             Skipped 2 entries: 1 dropped by rules, 1 already in the new version.\n",
+            COSMIC_COMP
+        ));
+    assert!(!v2.join(ENTRY_AUTOTILE).exists());
+    assert!(!state_home.join("cosmic-ctl").join("journal.jsonl").exists());
+
+    migrate(&[]).assert().success();
+    assert_eq!(
+        fs::read_to_string(v2.join(ENTRY_AUTOTILE)).unwrap(),
+        VALUE_TRUE
+    );
+    assert_eq!(fs::read_to_string(v2.join("new_name")).unwrap(), "42");
+    assert_eq!(fs::read_to_string(v2.join("delay")).unwrap(), "Some(250)");
+    assert_eq!(fs::read_to_string(v2.join("shared")).unwrap(), "2");
+    assert!(!v2.join("obsolete").exists());
+    assert!(!v2.join("old_name").exists());
+
+    let journal = fs::read_to_string(state_home.join("cosmic-ctl").join("journal.jsonl")).unwrap();
+    let records: Vec<serde_json::Value> = journal
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(records.len(), 3);
+    assert!(records.iter().all(|record| record["command"] == "migrate"));
+    assert_eq!(
+        records[2]["address"],
+        format!("{}/v2/new_name", COSMIC_COMP)
+    );
+
+    migrate(&["--overwrite"]).assert().success().stdout(format!(
+        "Dropping {0}/v1/obsolete\n\
+             Copying {0}/v1/shared to v2/shared\n\
+             Migrated 1 entries from v1 to v2.\n\
+             Skipped 4 entries: 3 unchanged, 1 dropped by rules.\n",
+        COSMIC_COMP
+    ));
+    assert_eq!(fs::read_to_string(v2.join("shared")).unwrap(), "1");
+}
+
+#[test]
+fn test_version_defaults_to_latest() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().join("config");
+    let data_dir = temp_dir.path().join("data");
+    let cosmic_ctl = || {
+        let mut command = Command::cargo_bin("cosmic-ctl").unwrap();
+        command
+            .env("XDG_CONFIG_HOME", &config_home)
+            .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+            .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+            .env("XDG_DATA_DIRS", &data_dir);
+        command
+    };
+
+    // New components start at v1.
+    cosmic_ctl()
+        .args([
+            WRITE_OPERATION,
+            "--component",
+            COSMIC_COMP,
+            "--entry",
+            ENTRY_AUTOTILE,
+            VALUE_TRUE,
+        ])
+        .assert()
+        .success()
+        .stderr("");
+    assert!(config_home
+        .join("cosmic")
+        .join(COSMIC_COMP)
+        .join("v1")
+        .join(ENTRY_AUTOTILE)
+        .exists());
+
+    // Versions shipped in the system defaults count too.
+    let default_v2 = data_dir.join("cosmic").join(COSMIC_COMP).join("v2");
+    fs::create_dir_all(&default_v2).unwrap();
+    fs::write(default_v2.join(ENTRY_AUTOTILE), "false").unwrap();
+
+    let warning = format!(
+        "Warning: {} has configuration versions v1, v2, using v2.\n",
+        COSMIC_COMP
+    );
+    cosmic_ctl()
+        .args([
+            WRITE_OPERATION,
+            "--component",
+            COSMIC_COMP,
+            "--entry",
+            ENTRY_AUTOTILE,
+            VALUE_TRUE,
+        ])
+        .assert()
+        .success()
+        .stderr(warning.clone());
+    cosmic_ctl()
+        .args([
+            READ_OPERATION,
+            "--component",
+            COSMIC_COMP,
+            "--entry",
+            ENTRY_AUTOTILE,
+        ])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", VALUE_TRUE))
+        .stderr(warning.clone());
+    assert_eq!(
+        fs::read_to_string(
+            config_home
+                .join("cosmic")
+                .join(COSMIC_COMP)
+                .join("v2")
+                .join(ENTRY_AUTOTILE)
+        )
+        .unwrap(),
+        VALUE_TRUE
+    );
+
+    // An explicit version is used as-is, without a warning.
+    cosmic_ctl()
+        .args([
+            DELETE_OPERATION,
+            "--component",
+            COSMIC_COMP,
+            "--entry",
+            ENTRY_AUTOTILE,
+            "--version",
+            "1",
+        ])
+        .assert()
+        .success()
+        .stderr("");
+
+    for (name, contents) in [
+        (
+            "latest.json",
+            json!({
+                "operations": [{
+                    "component": COSMIC_COMP,
+                    "version": "latest",
+                    "operation": WRITE_OPERATION,
+                    "entries": { ENTRY_AUTOTILE_BEHAVIOR: VALUE_PER_WORKSPACE }
+                }]
+            })
+            .to_string(),
+        ),
+        (
+            "latest.ron",
+            format!(
+                "(operations: [(component: Some(\"{}\"), version: Some(\"latest\"), operation: write, entries: Some({{ \"{}\": \"{}\" }}))])",
+                COSMIC_COMP, ENTRY_AUTOTILE_BEHAVIOR, VALUE_PER_WORKSPACE
+            ),
+        ),
+    ] {
+        let apply_file = temp_dir.path().join(name);
+        fs::write(&apply_file, contents).unwrap();
+
+        cosmic_ctl()
+            .args([APPLY_OPERATION])
+            .arg(&apply_file)
+            .assert()
+            .success()
+            .stderr(warning.clone());
+    }
+    assert_eq!(
+        fs::read_to_string(
+            config_home
+                .join("cosmic")
+                .join(COSMIC_COMP)
+                .join("v2")
+                .join(ENTRY_AUTOTILE_BEHAVIOR)
+        )
+        .unwrap(),
+        VALUE_PER_WORKSPACE
+    );
+
+    let apply_file = temp_dir.path().join("invalid.json");
+    fs::write(
+        &apply_file,
+        json!({
+            "operations": [{
+                "component": COSMIC_COMP,
+                "version": "newest",
+                "operation": WRITE_OPERATION,
+                "entries": { ENTRY_AUTOTILE: VALUE_TRUE }
+            }]
+        })
+        .to_string(),
+    )
+    .unwrap();
+    cosmic_ctl()
+        .args([APPLY_OPERATION])
+        .arg(&apply_file)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_cp_and_mv_commands() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().join("config");
+    let state_home = temp_dir.path().join("state");
+    let panel = "com.system76.CosmicPanel.Panel";
+    let dock = "com.system76.CosmicPanel.Dock";
+
+    let entry_path = |home: &std::path::Path, component: &str, entry: &str| {
+        home.join("cosmic").join(component).join("v1").join(entry)
+    };
+    for (entry, value) in [("anchor", "Top"), ("size", "XS"), ("opacity", "1.0")] {
+        let path = entry_path(&config_home, panel, entry);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, value).unwrap();
+    }
+    let dock_anchor = entry_path(&config_home, dock, "anchor");
+    fs::create_dir_all(dock_anchor.parent().unwrap()).unwrap();
+    fs::write(&dock_anchor, "Bottom").unwrap();
+
+    let cosmic_ctl = || {
+        let mut command = Command::cargo_bin("cosmic-ctl").unwrap();
+        command
+            .env("XDG_CONFIG_HOME", &config_home)
+            .env("XDG_STATE_HOME", &state_home)
+            .env("XDG_RUNTIME_DIR", &state_home);
+        command
+    };
+
+    // Existing entries with other values stop the copy before anything is written, unless
+    // told what to do.
+    cosmic_ctl()
+        .args(["cp", &format!("{}/v1/*", panel), &format!("{}/v1", dock)])
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(format!(
+            "Error: [config] {}/v1/anchor already exists with a different value\n\
+             Error: 1 entries already exist, use --overwrite to replace them or --no-clobber to skip them\n",
+            dock
+        ));
+    assert_eq!(fs::read_to_string(&dock_anchor).unwrap(), "Bottom");
+
+    cosmic_ctl()
+        .args([
+            "cp",
+            "--no-clobber",
+            &format!("{}/v1/*", panel),
+            &format!("{}/v1", dock),
+        ])
+        .assert()
+        .success()
+        .stdout(format!(
+            "Copied [config] {0}/v1/opacity to [config] {1}/v1/opacity\n\
+             Copied [config] {0}/v1/size to [config] {1}/v1/size\n\
+             Copied 2 entries.\n\
+             Skipped 1 entries: 1 already existing.\n",
+            panel, dock
+        ));
+
+    cosmic_ctl()
+        .args([
+            "cp",
+            "--overwrite",
+            &format!("{}/v1/anch?r", panel),
+            &format!("{}/v1", dock),
+        ])
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(&dock_anchor).unwrap(), "Top");
+
+    // Single entries can be renamed and moved between XDG directories.
+    cosmic_ctl()
+        .args([
+            "mv",
+            &format!("{}/v1/opacity", dock),
+            &format!("state:{}/v1/saved_opacity", dock),
+        ])
+        .assert()
+        .success()
+        .stdout(format!(
+            "Moved [config] {0}/v1/opacity to [state] {0}/v1/saved_opacity\nMoved 1 entries.\n",
+            dock
+        ));
+    assert!(!entry_path(&config_home, dock, "opacity").exists());
+    assert_eq!(
+        fs::read_to_string(entry_path(&state_home, dock, "saved_opacity")).unwrap(),
+        "1.0"
+    );
+
+    cosmic_ctl()
+        .args(["cp", &format!("{}/v1/*", panel), &format!("{}/v1/anchor", dock)])
+        .assert()
+        .failure()
+        .stderr(format!(
+            "Error: The destination must be a component version (e.g., '{}/v1') when the source is a pattern\n",
+            dock
+        ));
+    cosmic_ctl()
+        .args([
+            "cp",
+            &format!("{}/v1/missing*", panel),
+            &format!("{}/v1", dock),
+        ])
+        .assert()
+        .failure()
+        .stderr(format!(
+            "Error: No entries match [config] {}/v1/missing*\n",
+            panel
+        ));
+    cosmic_ctl()
+        .args([
+            "mv",
+            &format!("{}/v1/size", panel),
+            &format!("{}/v1", panel),
+        ])
+        .assert()
+        .failure()
+        .stderr(format!(
+            "Error: [config] {}/v1/size cannot be copied onto itself\n",
+            panel
+        ));
+}
+
+#[test]
+fn test_export_and_import_tree() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().join("config");
+    let state_home = temp_dir.path().join("state");
+    let tree = temp_dir.path().join("tree");
+
+    let comp_dir = config_home.join("cosmic").join(COSMIC_COMP).join("v1");
+    fs::create_dir_all(&comp_dir).unwrap();
+    fs::write(comp_dir.join(ENTRY_AUTOTILE), VALUE_TRUE).unwrap();
+    fs::write(comp_dir.join(ENTRY_XKB_CONFIG), VALUE_XKB_CONFIG).unwrap();
+    let panel_dir = state_home
+        .join("cosmic")
+        .join("com.system76.CosmicPanel")
+        .join("v1");
+    fs::create_dir_all(&panel_dir).unwrap();
+    fs::write(panel_dir.join("hidden"), "false").unwrap();
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", &config_home)
+        .env("XDG_STATE_HOME", &state_home)
+        .env("XDG_RUNTIME_DIR", &state_home)
+        .args([
+            "export",
+            "--exclude",
+            &format!("{}/v1/{}", COSMIC_COMP, ENTRY_XKB_CONFIG),
+        ])
+        .arg("--tree")
+        .arg(&tree)
+        .assert()
+        .success()
+        .stdout(format!(
+            "Exported 2 entries to {}.\nSkipped 1 entries: 1 excluded.\n",
+            tree.display()
+        ));
+
+    let exported_autotile = tree
+        .join("config")
+        .join("cosmic")
+        .join(COSMIC_COMP)
+        .join("v1")
+        .join(ENTRY_AUTOTILE);
+    assert_eq!(fs::read_to_string(&exported_autotile).unwrap(), VALUE_TRUE);
+    assert_eq!(
+        fs::read_to_string(
+            tree.join("state")
+                .join("cosmic")
+                .join("com.system76.CosmicPanel")
+                .join("v1")
+                .join("hidden")
+        )
+        .unwrap(),
+        "false"
+    );
+    assert!(!tree
+        .join("config")
+        .join("cosmic")
+        .join(COSMIC_COMP)
+        .join("v1")
+        .join(ENTRY_XKB_CONFIG)
+        .exists());
+
+    fs::write(&exported_autotile, "false").unwrap();
+    fs::write(tree.join("README"), "not an entry").unwrap();
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", &config_home)
+        .env("XDG_STATE_HOME", &state_home)
+        .env("XDG_RUNTIME_DIR", &state_home)
+        .args(["import", "--verbose"])
+        .arg("--tree")
+        .arg(&tree)
+        .assert()
+        .success()
+        .stdout(format!(
+            "Skipping README - not an <xdg>/cosmic/<component>/vN/<entry> file\n\
+             Wrote [config] {}/v1/{}\n\
+             Skipping [state] com.system76.CosmicPanel/v1/hidden - value unchanged\n\
+             Import completed. 1 entries written.\n\
+             Skipped 2 entries: 1 unrecognised, 1 unchanged.\n",
+            COSMIC_COMP, ENTRY_AUTOTILE
+        ));
+    assert_eq!(
+        fs::read_to_string(comp_dir.join(ENTRY_AUTOTILE)).unwrap(),
+        "false"
+    );
+    assert_eq!(
+        fs::read_to_string(comp_dir.join(ENTRY_XKB_CONFIG)).unwrap(),
+        VALUE_XKB_CONFIG
+    );
+}
+
+#[test]
+fn test_nix_backup() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().join("config");
+    let version_path = config_home
+        .join("cosmic")
+        .join(COSMIC_COMP)
+        .join(format!("v{}", VERSION_1));
+    fs::create_dir_all(&version_path).unwrap();
+    fs::write(version_path.join(ENTRY_AUTOTILE), VALUE_TRUE).unwrap();
+    fs::write(version_path.join("command"), "\"${HOME}/bin\"").unwrap();
+    fs::write(
+        version_path.join("xkb_config"),
+        "(\n    rules: \"${x}\",\n)",
+    )
+    .unwrap();
+    fs::write(version_path.join("binary"), [0xff, 0xfe]).unwrap();
+    let state_version_path = temp_dir
+        .path()
+        .join("state")
+        .join("cosmic")
+        .join(COSMIC_COMP)
+        .join(format!("v{}", VERSION_1));
+    fs::create_dir_all(&state_version_path).unwrap();
+    fs::write(state_version_path.join(ENTRY_AUTOTILE), "false").unwrap();
+    let backup_file = temp_dir.path().join("backup.nix");
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .env("XDG_CONFIG_HOME", &config_home)
+        .arg(BACKUP_OPERATION)
+        .arg(&backup_file)
+        .assert()
+        .success()
+        .stdout(
+            "Backup completed successfully. 4 total entries backed up in Nix format.\n\
+             Skipped 1 entries: 1 not valid UTF-8.\n",
+        );
+
+    assert_eq!(
+        fs::read_to_string(&backup_file).unwrap(),
+        r#"{
+  "com.system76.CosmicComp" = {
+    v1 = {
+      autotile = "true";
+      command = "\"\${HOME}/bin\"";
+      xkb_config = ''
+        (
+            rules: "''${x}",
+        )'';
+    };
+  };
+  xdg = {
+    state = {
+      "com.system76.CosmicComp" = {
+        v1 = {
+          autotile = "false";
+        };
+      };
+    };
+  };
+}"#
+    );
+
+    let output = Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .arg(APPLY_OPERATION)
+        .arg(&backup_file)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("only written by backups"));
+}
+
+#[test]
+fn test_convert_between_all_formats() {
+    let temp_dir = TempDir::new().unwrap();
+    let original = json!({
+        "$schema": "https://raw.githubusercontent.com/cosmic-utils/cosmic-ctl/refs/heads/main/schema.json",
+        "operations": [
+            {
+                "component": COSMIC_COMP,
+                "version": 1,
+                "operation": "write",
+                "entries": { ENTRY_AUTOTILE: VALUE_TRUE, "xkb_config": "(\n    rules: \"\",\n)" },
+                "xdg_directory": XDG_CONFIG_DIR
+            },
+            {
+                "component": COSMIC_COMP,
+                "version": "latest",
+                "operation": "delete",
+                "entries": [ENTRY_AUTOTILE_BEHAVIOR]
+            },
+            {
+                "component": COSMIC_COMP,
+                "version": 1,
+                "operation": "write",
+                "entries": { "binary": "AP/+XG6A" },
+                "encoding": "base64"
+            },
+            {
+                "operation": "write",
+                "file": "~/.config/cosmic-ctl-test.conf",
+                "value": VALUE_TRUE
+            }
+        ]
+    });
+    let original_file = temp_dir.path().join("original.json");
+    fs::write(&original_file, original.to_string()).unwrap();
+
+    let convert = |input: &std::path::Path, output: &std::path::Path| {
+        Command::cargo_bin("cosmic-ctl")
+            .unwrap()
+            .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+            .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+            .arg("convert")
+            .arg(input)
+            .arg(output)
+            .output()
+            .unwrap()
+    };
+
+    let formats = ["json", "toml", "ron"];
+    for from in formats {
+        let source = temp_dir.path().join(format!("source.{}", from));
+        assert!(convert(&original_file, &source).status.success());
+
+        for to in formats {
+            let converted = temp_dir.path().join(format!("{}-to.{}", from, to));
+            let output = convert(&source, &converted);
+            assert!(output.status.success(), "{} to {}", from, to);
+
+            let expected_warning = if from != "ron" && to == "ron" {
+                "Warning: dropped $schema, RON files can't reference a JSON schema\n"
+            } else {
+                ""
+            };
+            assert_eq!(
+                String::from_utf8_lossy(&output.stderr),
+                expected_warning,
+                "{} to {}",
+                from,
+                to
+            );
+            assert_eq!(
+                fs::read_to_string(&converted).unwrap().contains("$schema"),
+                to != "ron",
+                "{} to {}",
+                from,
+                to
+            );
+
+            let round_trip = temp_dir.path().join(format!("{}-to-{}.json", from, to));
+            assert!(convert(&converted, &round_trip).status.success());
+            let round_trip: serde_json::Value =
+                serde_json::from_str(&fs::read_to_string(&round_trip).unwrap()).unwrap();
+            assert_eq!(round_trip, original, "{} to {}", from, to);
+        }
+    }
+}
+
+#[test]
+fn test_convert_reports_lossy_conversions() {
+    let temp_dir = TempDir::new().unwrap();
+    let input_file = temp_dir.path().join("input.txt");
+    fs::write(
+        &input_file,
+        json!({
+            "comment": "shared settings",
+            "operations": [{
+                "component": COSMIC_COMP,
+                "version": 1,
+                "operation": "write",
+                "entries": { ENTRY_AUTOTILE: VALUE_TRUE },
+                "enabled": true,
+                "value": null
+            }]
+        })
+        .to_string(),
+    )
+    .unwrap();
+    let output_file = temp_dir.path().join("output.txt");
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .args(["convert", "--from", "json", "--to", "toml"])
+        .arg(&input_file)
+        .arg(&output_file)
+        .assert()
+        .success()
+        .stderr(
+            "Warning: dropped unknown field comment\n\
+             Warning: dropped unknown field operations[0].enabled\n",
+        );
+
+    let converted: toml::Value =
+        toml::from_str(&fs::read_to_string(&output_file).unwrap()).unwrap();
+    assert_eq!(
+        converted["operations"][0]["entries"][ENTRY_AUTOTILE].as_str(),
+        Some(VALUE_TRUE)
+    );
+    assert!(converted.get("comment").is_none());
+
+    let output = Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .args(["convert", "--from", "json", "--to", "nix"])
+        .arg(&input_file)
+        .arg(&output_file)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("use 'backup --format nix'"));
+}
+
+#[test]
+fn test_edit_command_unchanged_invalid_value() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().join("config");
+    let version_path = config_home
+        .join("cosmic")
+        .join(COSMIC_COMP)
+        .join(format!("v{}", VERSION_1));
+    fs::create_dir_all(&version_path).unwrap();
+    fs::write(version_path.join(ENTRY_AUTOTILE), "(").unwrap();
+
+    for editor in ["true", "truncate -s 0"] {
+        Command::cargo_bin("cosmic-ctl")
+            .unwrap()
+            .env("XDG_CONFIG_HOME", &config_home)
+            .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+            .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+            .env("VISUAL", editor)
+            .args([
+                "edit",
+                "--component",
+                COSMIC_COMP,
+                "--entry",
+                ENTRY_AUTOTILE,
+                "--yes",
+            ])
+            .timeout(std::time::Duration::from_secs(10))
+            .assert()
+            .success()
+            .stdout("Doing nothing. No changes were made.\n");
+
+        assert_eq!(
+            fs::read_to_string(version_path.join(ENTRY_AUTOTILE)).unwrap(),
+            "("
+        );
+    }
+}
+
+#[test]
+fn test_symlinks_confined_to_root() {
+    use std::os::unix::fs::symlink;
+
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("root");
+    let version_path = root
+        .join("etc/skel/.config/cosmic")
+        .join(COSMIC_COMP)
+        .join(format!("v{}", VERSION_1));
+    fs::create_dir_all(&version_path).unwrap();
+    let host_file = temp_dir.path().join("host").join(ENTRY_AUTOTILE);
+    fs::create_dir_all(host_file.parent().unwrap()).unwrap();
+    fs::write(&host_file, "false").unwrap();
+    let image_file = root.join(host_file.strip_prefix("/").unwrap());
+    fs::create_dir_all(image_file.parent().unwrap()).unwrap();
+    fs::write(&image_file, "false").unwrap();
+    symlink(&host_file, version_path.join(ENTRY_AUTOTILE)).unwrap();
+    symlink(
+        "../../../../../../../outside",
+        version_path.join(ENTRY_AUTOTILE_BEHAVIOR),
+    )
+    .unwrap();
+
+    let write = |entry: &str| {
+        Command::cargo_bin("cosmic-ctl")
+            .unwrap()
+            .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+            .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+            .args(["--root", root.to_str().unwrap(), "--home", "/etc/skel"])
+            .args([
+                WRITE_OPERATION,
+                "--component",
+                COSMIC_COMP,
+                "--entry",
+                entry,
+            ])
+            .arg(VALUE_TRUE)
+            .output()
+            .unwrap()
+    };
+
+    assert!(write(ENTRY_AUTOTILE).status.success());
+    assert_eq!(fs::read_to_string(&image_file).unwrap(), VALUE_TRUE);
+    assert_eq!(fs::read_to_string(&host_file).unwrap(), "false");
+
+    let output = write(ENTRY_AUTOTILE_BEHAVIOR);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("points outside the root directory"));
+    assert!(!temp_dir.path().join("outside").exists());
+}
+
+#[test]
+fn test_delete_and_reset_dangling_symlinks() {
+    use std::os::unix::fs::symlink;
+
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().join("config");
+    let version_path = config_home
+        .join("cosmic")
+        .join(COSMIC_COMP)
+        .join(format!("v{}", VERSION_1));
+    fs::create_dir_all(&version_path).unwrap();
+    let missing = temp_dir.path().join("missing");
+    symlink(&missing, version_path.join(ENTRY_AUTOTILE)).unwrap();
+    symlink(&missing, version_path.join(ENTRY_AUTOTILE_BEHAVIOR)).unwrap();
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", &config_home)
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .args([
+            DELETE_OPERATION,
+            "--component",
+            COSMIC_COMP,
+            "--entry",
+            ENTRY_AUTOTILE,
+        ])
+        .assert()
+        .success();
+    assert!(fs::symlink_metadata(version_path.join(ENTRY_AUTOTILE)).is_err());
+
+    let output = Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", &config_home)
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .args(["reset", "--force", "--xdg-dirs", XDG_CONFIG_DIR])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout)
+        .contains("Successfully deleted 1 configuration entries."));
+    assert!(fs::symlink_metadata(version_path.join(ENTRY_AUTOTILE_BEHAVIOR)).is_err());
+}
+
+#[test]
+fn test_user_override_owns_replaced_entries() {
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+    let root_dir = TempDir::new().unwrap();
+    let owner = fs::metadata(root_dir.path()).unwrap();
+
+    fs::create_dir_all(root_dir.path().join("etc")).unwrap();
+    fs::write(
+        root_dir.path().join("etc/passwd"),
+        format!(
+            "root:x:0:0:root:/root:/bin/sh\ntester:x:{}:{}:Tester:/home/tester:/bin/sh\n",
+            owner.uid(),
+            owner.gid()
+        ),
+    )
+    .unwrap();
+    let config_path = root_dir
+        .path()
+        .join("home/tester/.config/cosmic")
+        .join(COSMIC_COMP)
+        .join(format!("v{}", VERSION_1))
+        .join(ENTRY_AUTOTILE);
+    fs::create_dir_all(config_path.parent().unwrap()).unwrap();
+    fs::write(&config_path, "false").unwrap();
+    fs::set_permissions(&config_path, fs::Permissions::from_mode(0o640)).unwrap();
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_STATE_HOME", root_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", root_dir.path().join("state"))
+        .args([
+            "--root",
+            root_dir.path().to_str().unwrap(),
+            "--user",
+            "tester",
+        ])
+        .args([
+            WRITE_OPERATION,
+            "--component",
+            COSMIC_COMP,
+            "--entry",
+            ENTRY_AUTOTILE,
+            VALUE_TRUE,
+        ])
+        .assert()
+        .success()
+        .stderr("");
+
+    let metadata = fs::metadata(&config_path).unwrap();
+    assert_eq!((metadata.uid(), metadata.gid()), (owner.uid(), owner.gid()));
+    assert_eq!(metadata.permissions().mode() & 0o777, 0o640);
+    assert_eq!(fs::read_to_string(&config_path).unwrap(), VALUE_TRUE);
+}
+
+#[test]
+fn test_journal_settings_from_environment() {
+    let temp_dir = TempDir::new().unwrap();
+    let state_home = temp_dir.path().join("state");
+
+    for value in ["1", "2", "3"] {
+        Command::cargo_bin("cosmic-ctl")
+            .unwrap()
+            .env("XDG_CONFIG_HOME", temp_dir.path().join("config"))
+            .env("XDG_STATE_HOME", &state_home)
+            .env("XDG_RUNTIME_DIR", &state_home)
+            .env("COSMIC_CTL_JOURNAL_MAX_SIZE", "1")
+            .env("COSMIC_CTL_JOURNAL_MAX_FILES", "1")
+            .args([
+                WRITE_OPERATION,
+                "--component",
+                COSMIC_COMP,
+                "--entry",
+                ENTRY_AUTOTILE,
+                value,
+            ])
+            .assert()
+            .success();
+    }
+
+    let journal_dir = state_home.join("cosmic-ctl");
+    assert!(journal_dir.join("journal.jsonl.1").exists());
+    assert!(!journal_dir.join("journal.jsonl.2").exists());
+
+    let other_state_home = temp_dir.path().join("other-state");
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", temp_dir.path().join("config"))
+        .env("XDG_STATE_HOME", &other_state_home)
+        .env("XDG_RUNTIME_DIR", &other_state_home)
+        .env("COSMIC_CTL_NO_JOURNAL", "true")
+        .args([
+            WRITE_OPERATION,
+            "--component",
+            COSMIC_COMP,
+            "--entry",
+            ENTRY_AUTOTILE,
+            "4",
+        ])
+        .assert()
+        .success();
+    assert!(!other_state_home
+        .join("cosmic-ctl")
+        .join("journal.jsonl")
+        .exists());
+}
+
+#[test]
+fn test_user_override_reads_environment() {
+    use std::os::unix::fs::MetadataExt;
+
+    let root_dir = TempDir::new().unwrap();
+    let owner = fs::metadata(root_dir.path()).unwrap();
+    fs::create_dir_all(root_dir.path().join("etc")).unwrap();
+    fs::write(
+        root_dir.path().join("etc/passwd"),
+        format!(
+            "tester:x:{}:{}:Tester:/home/tester:/bin/sh\n",
+            owner.uid(),
+            owner.gid()
+        ),
+    )
+    .unwrap();
+    let environment_dir = root_dir.path().join("home/tester/.config/environment.d");
+    fs::create_dir_all(&environment_dir).unwrap();
+    fs::write(
+        environment_dir.join("10-xdg.conf"),
+        "# Dotfiles\nHOMEDIR=$HOME/dotfiles\nDOTFILES=$HOMEDIR\nXDG_CONFIG_HOME=${DOTFILES}/config\nXDG_STATE_HOME=relative/state\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_RUNTIME_DIR", root_dir.path())
+        .args([
+            "--root",
+            root_dir.path().to_str().unwrap(),
+            "--user",
+            "tester",
+        ])
+        .args([
+            WRITE_OPERATION,
+            "--component",
+            COSMIC_COMP,
+            "--entry",
+            ENTRY_AUTOTILE,
+            VALUE_TRUE,
+        ])
+        .assert()
+        .success();
+
+    let config_path = root_dir
+        .path()
+        .join("home/tester/dotfiles/config/cosmic")
+        .join(COSMIC_COMP)
+        .join(format!("v{}", VERSION_1))
+        .join(ENTRY_AUTOTILE);
+    assert_eq!(fs::read_to_string(config_path).unwrap(), VALUE_TRUE);
+
+    // Relative paths are ignored, so state entries go to the default state directory.
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_RUNTIME_DIR", root_dir.path())
+        .args([
+            "--root",
+            root_dir.path().to_str().unwrap(),
+            "--user",
+            "tester",
+        ])
+        .args([
+            WRITE_OPERATION,
+            "--xdg-dir",
+            "state",
+            "--component",
+            COSMIC_COMP,
+            "--entry",
+            ENTRY_AUTOTILE,
+            VALUE_TRUE,
+        ])
+        .assert()
+        .success();
+    assert!(root_dir
+        .path()
+        .join("home/tester/.local/state/cosmic")
+        .join(COSMIC_COMP)
+        .join(format!("v{}", VERSION_1))
+        .join(ENTRY_AUTOTILE)
+        .exists());
+
+    // Without --root, users come from the system's user database.
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .args(["--user", "cosmic-ctl-no-such-user", "log"])
+        .assert()
+        .failure()
+        .stderr("Error: Unknown user: cosmic-ctl-no-such-user\n");
+}
+
+#[test]
+fn test_user_override_owns_exported_files() {
+    use std::os::unix::fs::MetadataExt;
+
+    let root_dir = TempDir::new().unwrap();
+    let owner = fs::metadata(root_dir.path()).unwrap();
+
+    fs::create_dir_all(root_dir.path().join("etc")).unwrap();
+    fs::write(
+        root_dir.path().join("etc/passwd"),
+        format!(
+            "tester:x:{}:{}:Tester:/home/tester:/bin/sh\n",
+            owner.uid(),
+            owner.gid()
+        ),
+    )
+    .unwrap();
+    let version_path = root_dir
+        .path()
+        .join("home/tester/.config/cosmic")
+        .join(COSMIC_COMP)
+        .join(format!("v{}", VERSION_1));
+    fs::create_dir_all(&version_path).unwrap();
+    fs::write(version_path.join(ENTRY_AUTOTILE), VALUE_TRUE).unwrap();
+
+    let output_dir = root_dir.path().join("output");
+    let backup_file = root_dir.path().join("backup.json");
+    for args in [
+        vec!["export", "--tree", output_dir.to_str().unwrap()],
+        vec![BACKUP_OPERATION, backup_file.to_str().unwrap()],
+    ] {
+        Command::cargo_bin("cosmic-ctl")
+            .unwrap()
+            .env("XDG_RUNTIME_DIR", root_dir.path())
+            .args([
+                "--root",
+                root_dir.path().to_str().unwrap(),
+                "--user",
+                "tester",
+            ])
+            .args(args)
+            .assert()
+            .success();
+    }
+
+    let exported_file = output_dir
+        .join("config/cosmic")
+        .join(COSMIC_COMP)
+        .join(format!("v{}", VERSION_1))
+        .join(ENTRY_AUTOTILE);
+    for path in [&output_dir, &exported_file, &backup_file] {
+        let metadata = fs::metadata(path).unwrap();
+        assert_eq!((metadata.uid(), metadata.gid()), (owner.uid(), owner.gid()));
+    }
+}
+
+#[test]
+fn test_reset_prompt_does_not_hold_lock() {
+    use std::io::{Read, Write};
+    use std::process::Stdio;
+
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().join("config");
+    let state_home = temp_dir.path().join("state");
+    let write = |value: &str| {
+        Command::cargo_bin("cosmic-ctl")
+            .unwrap()
+            .env("XDG_CONFIG_HOME", &config_home)
+            .env("XDG_STATE_HOME", &state_home)
+            .env("XDG_RUNTIME_DIR", temp_dir.path())
+            .args([
+                WRITE_OPERATION,
+                "--component",
+                COSMIC_COMP,
+                "--entry",
+                ENTRY_AUTOTILE,
+                value,
+            ])
+            .assert()
+            .success();
+    };
+    write(VALUE_TRUE);
+
+    let mut reset = std::process::Command::new(assert_cmd::cargo::cargo_bin("cosmic-ctl"))
+        .env("XDG_CONFIG_HOME", &config_home)
+        .env("XDG_STATE_HOME", &state_home)
+        .env("XDG_RUNTIME_DIR", temp_dir.path())
+        .args(["reset", "--xdg-dirs", XDG_CONFIG_DIR])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut prompt = Vec::new();
+    let mut stdout = reset.stdout.take().unwrap();
+    while !String::from_utf8_lossy(&prompt).contains("[y/N]") {
+        let mut chunk = [0; 256];
+        let read = stdout.read(&mut chunk).unwrap();
+        assert!(read > 0, "reset exited before prompting");
+        prompt.extend_from_slice(&chunk[..read]);
+    }
+
+    write("false");
+
+    reset.stdin.take().unwrap().write_all(b"n\n").unwrap();
+    assert!(reset.wait().unwrap().success());
+}
+
+#[test]
+fn test_latest_version_ignores_defaults_outside_config() {
+    let temp_dir = TempDir::new().unwrap();
+    let data_dir = temp_dir.path().join("data_dirs");
+    fs::create_dir_all(
+        data_dir
+            .join("cosmic")
+            .join(COSMIC_COMP)
+            .join(format!("v{}", VERSION_2)),
+    )
+    .unwrap();
+
+    for xdg_dir in [XDG_CONFIG_DIR, XDG_STATE_DIR] {
+        Command::cargo_bin("cosmic-ctl")
+            .unwrap()
+            .env("XDG_CONFIG_HOME", temp_dir.path().join("config"))
+            .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+            .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+            .env("XDG_DATA_DIRS", &data_dir)
+            .args([
+                WRITE_OPERATION,
+                "--xdg-dir",
+                xdg_dir,
+                "--component",
+                COSMIC_COMP,
+                "--entry",
+                ENTRY_AUTOTILE,
+                VALUE_TRUE,
+            ])
+            .assert()
+            .success();
+    }
+
+    let entry_path = |xdg_dir: &str, version: u64| {
+        temp_dir
+            .path()
+            .join(xdg_dir)
+            .join("cosmic")
+            .join(COSMIC_COMP)
+            .join(format!("v{}", version))
+            .join(ENTRY_AUTOTILE)
+    };
+    assert!(entry_path(XDG_CONFIG_DIR, VERSION_2).exists());
+    assert!(entry_path(XDG_STATE_DIR, VERSION_1).exists());
+    assert!(!entry_path(XDG_STATE_DIR, VERSION_2).exists());
+}
+
+#[test]
+fn test_mv_failure_keeps_both_trees() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().join("config");
+    let panel = "com.system76.CosmicPanel.Panel";
+    let dock = "com.system76.CosmicPanel.Dock";
+
+    let entry_path = |component: &str, entry: &str| {
+        config_home
+            .join("cosmic")
+            .join(component)
+            .join("v1")
+            .join(entry)
+    };
+    for (entry, value) in [("anchor", "Top"), ("opacity", "1.0"), ("size", "XS")] {
+        let path = entry_path(panel, entry);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, value).unwrap();
+    }
+    fs::create_dir_all(entry_path(dock, "anchor").parent().unwrap()).unwrap();
+    fs::write(entry_path(dock, "anchor"), "Bottom").unwrap();
+    // The last entry can't be written, after the others have been.
+    fs::write(entry_path(dock, "size"), "XL").unwrap();
+    fs::set_permissions(entry_path(dock, "size"), fs::Permissions::from_mode(0o444)).unwrap();
+
+    let output = Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", &config_home)
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .args([
+            "mv",
+            "--overwrite",
+            &format!("{}/v1/*", panel),
+            &format!("{}/v1", dock),
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("read-only"));
+
+    for (entry, value) in [("anchor", "Top"), ("opacity", "1.0"), ("size", "XS")] {
+        assert_eq!(fs::read_to_string(entry_path(panel, entry)).unwrap(), value);
+    }
+    assert_eq!(
+        fs::read_to_string(entry_path(dock, "anchor")).unwrap(),
+        "Bottom"
+    );
+    assert!(!entry_path(dock, "opacity").exists());
+    assert_eq!(fs::read_to_string(entry_path(dock, "size")).unwrap(), "XL");
+}
+
+#[test]
+fn test_cp_into_another_users_tree() {
+    use std::os::unix::fs::MetadataExt;
+
+    let root_dir = TempDir::new().unwrap();
+    let owner = fs::metadata(root_dir.path()).unwrap();
+
+    fs::create_dir_all(root_dir.path().join("etc")).unwrap();
+    fs::write(
+        root_dir.path().join("etc/passwd"),
+        format!(
+            "root:x:0:0:root:/root:/bin/sh\ntester:x:{}:{}:Tester:/home/tester:/bin/sh\n",
+            owner.uid(),
+            owner.gid()
+        ),
+    )
+    .unwrap();
+    let panel_path = root_dir
+        .path()
+        .join("config/cosmic/com.system76.CosmicPanel.Panel/v1");
+    fs::create_dir_all(&panel_path).unwrap();
+    fs::write(panel_path.join("anchor"), "Top").unwrap();
+    fs::write(panel_path.join("size"), "XS").unwrap();
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", "/config")
+        .env("XDG_STATE_HOME", root_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", root_dir.path().join("state"))
+        .args(["--root", root_dir.path().to_str().unwrap()])
+        .args([
+            "cp",
+            "com.system76.CosmicPanel.Panel/v1/*",
+            "tester@com.system76.CosmicPanel.Panel/v1",
+        ])
+        .assert()
+        .success()
+        .stdout(
+            "Copied [config] com.system76.CosmicPanel.Panel/v1/anchor to [tester@config] com.system76.CosmicPanel.Panel/v1/anchor\n\
+             Copied [config] com.system76.CosmicPanel.Panel/v1/size to [tester@config] com.system76.CosmicPanel.Panel/v1/size\n\
+             Copied 2 entries.\n",
+        );
+
+    let tester_config = root_dir.path().join("home/tester/.config");
+    let copied = tester_config.join("cosmic/com.system76.CosmicPanel.Panel/v1/anchor");
+    assert_eq!(fs::read_to_string(&copied).unwrap(), "Top");
+    for path in [copied.as_path(), copied.parent().unwrap(), &tester_config] {
+        let metadata = fs::metadata(path).unwrap();
+        assert_eq!((metadata.uid(), metadata.gid()), (owner.uid(), owner.gid()));
+    }
+}
+
+#[test]
+fn test_user_override_refuses_symlinks_out_of_home() {
+    use std::os::unix::fs::{symlink, MetadataExt};
+
+    let root_dir = TempDir::new().unwrap();
+    let owner = fs::metadata(root_dir.path()).unwrap();
+    let passwd = format!(
+        "tester:x:{}:{}:Tester:/home/tester:/bin/sh\n",
+        owner.uid(),
+        owner.gid()
+    );
+    fs::create_dir_all(root_dir.path().join("etc")).unwrap();
+    fs::write(root_dir.path().join("etc/passwd"), &passwd).unwrap();
+    let version_path = root_dir
+        .path()
+        .join("home/tester/.config/cosmic")
+        .join(COSMIC_COMP)
+        .join(format!("v{}", VERSION_1));
+    fs::create_dir_all(&version_path).unwrap();
+    symlink("/etc/passwd", version_path.join(ENTRY_AUTOTILE)).unwrap();
+    fs::write(root_dir.path().join("home/tester/dotfile"), "false").unwrap();
+    symlink(
+        "../../../../dotfile",
+        version_path.join(ENTRY_AUTOTILE_BEHAVIOR),
+    )
+    .unwrap();
+
+    let write = |entry: &str| {
+        Command::cargo_bin("cosmic-ctl")
+            .unwrap()
+            .env("XDG_STATE_HOME", root_dir.path().join("state"))
+            .env("XDG_RUNTIME_DIR", root_dir.path())
+            .args([
+                "--root",
+                root_dir.path().to_str().unwrap(),
+                "--user",
+                "tester",
+            ])
+            .args([
+                WRITE_OPERATION,
+                "--component",
+                COSMIC_COMP,
+                "--entry",
+                entry,
+                VALUE_TRUE,
+            ])
+            .output()
+            .unwrap()
+    };
+
+    let output = write(ENTRY_AUTOTILE);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not the user's own file"));
+    assert_eq!(
+        fs::read_to_string(root_dir.path().join("etc/passwd")).unwrap(),
+        passwd
+    );
+
+    assert!(write(ENTRY_AUTOTILE_BEHAVIOR).status.success());
+    assert_eq!(
+        fs::read_to_string(root_dir.path().join("home/tester/dotfile")).unwrap(),
+        VALUE_TRUE
+    );
+
+    // The journal and lock file are never followed to a symlink's target.
+    let temp_dir = TempDir::new().unwrap();
+    let journal_dir = temp_dir.path().join("state/cosmic-ctl");
+    fs::create_dir_all(&journal_dir).unwrap();
+    let target = temp_dir.path().join("target");
+    symlink(&target, journal_dir.join("journal.jsonl")).unwrap();
+    symlink(&target, journal_dir.join("lock")).unwrap();
+
+    Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", temp_dir.path().join("config"))
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .args([
+            WRITE_OPERATION,
+            "--component",
+            COSMIC_COMP,
+            "--entry",
+            ENTRY_AUTOTILE,
+            VALUE_TRUE,
+        ])
+        .assert()
+        .failure();
+    assert!(!target.exists());
+
+    fs::remove_file(journal_dir.join("lock")).unwrap();
+    let output = Command::cargo_bin("cosmic-ctl")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", temp_dir.path().join("config"))
+        .env("XDG_STATE_HOME", temp_dir.path().join("state"))
+        .env("XDG_RUNTIME_DIR", temp_dir.path().join("state"))
+        .args([
+            WRITE_OPERATION,
+            "--component",
+            COSMIC_COMP,
+            "--entry",
+            ENTRY_AUTOTILE,
+            VALUE_TRUE,
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("Warning: failed to record change in journal"));
+    assert!(!target.exists());
+}
+
+#[test]
+fn test_root_reads_stay_inside_root() {
+    use std::os::unix::fs::symlink;
+
+    let root_dir = TempDir::new().unwrap();
+    let host_dir = TempDir::new().unwrap();
+    let host_version_path = host_dir.path().join("v1");
+    fs::create_dir_all(&host_version_path).unwrap();
+    fs::write(host_dir.path().join("secret"), "host-secret").unwrap();
+    fs::write(host_version_path.join(ENTRY_AUTOTILE), "host-secret").unwrap();
+
+    let cosmic_path = root_dir.path().join("etc/skel/.config/cosmic");
+    let version_path = cosmic_path
+        .join(COSMIC_COMP)
+        .join(format!("v{}", VERSION_1));
+    fs::create_dir_all(&version_path).unwrap();
+    fs::write(version_path.join(ENTRY_AUTOTILE), VALUE_TRUE).unwrap();
+    // Absolute targets that only exist on the host, not inside the root.
+    symlink(
+        host_dir.path().join("secret"),
+        version_path.join(ENTRY_AUTOTILE_BEHAVIOR),
+    )
+    .unwrap();
+    symlink(
+        host_dir.path(),
+        cosmic_path.join("com.system76.CosmicPanel"),
+    )
+    .unwrap();
+
+    let cosmic_ctl = || {
+        let mut command = Command::cargo_bin("cosmic-ctl").unwrap();
+        command
+            .env("XDG_STATE_HOME", root_dir.path().join("state"))
+            .env("XDG_RUNTIME_DIR", root_dir.path().join("state"))
+            .args([
+                "--root",
+                root_dir.path().to_str().unwrap(),
+                "--home",
+                "/etc/skel",
+            ]);
+        command
+    };
+
+    let backup_file = root_dir.path().join("backup.json");
+    assert!(cosmic_ctl()
+        .arg(BACKUP_OPERATION)
+        .arg(&backup_file)
+        .output()
+        .unwrap()
+        .status
+        .success());
+    let backup = fs::read_to_string(&backup_file).unwrap();
+    assert!(backup.contains(ENTRY_AUTOTILE));
+    assert!(!backup.contains("host-secret"));
+
+    let output = cosmic_ctl()
+        .args(["search", "host-secret"])
+        .output()
+        .unwrap();
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("host-secret"));
+
+    cosmic_ctl()
+        .args([
+            READ_OPERATION,
+            "--component",
+            COSMIC_COMP,
+            "--entry",
+            ENTRY_AUTOTILE_BEHAVIOR,
+        ])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_migrate_rules_are_validated_and_failures_skipped() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().join("config");
+    let state_home = temp_dir.path().join("state");
+    let component_dir = config_home.join("cosmic").join(COSMIC_COMP);
+
+    let v1 = component_dir.join("v1");
+    fs::create_dir_all(&v1).unwrap();
+    fs::write(v1.join(ENTRY_AUTOTILE), VALUE_TRUE).unwrap();
+    fs::write(v1.join("binary"), [0xff, 0xfe]).unwrap();
+    let v2 = component_dir.join("v2");
+
+    let rules_file = temp_dir.path().join("rules.json");
+    let migrate = |rules: serde_json::Value, extra_args: &[&str]| {
+        fs::write(&rules_file, rules.to_string()).unwrap();
+        let mut command = Command::cargo_bin("cosmic-ctl").unwrap();
+        command
+            .env("XDG_CONFIG_HOME", &config_home)
+            .env("XDG_STATE_HOME", &state_home)
+            .env("XDG_RUNTIME_DIR", &state_home)
+            .args([
+                "migrate",
+                "--component",
+                COSMIC_COMP,
+                "--from",
+                "1",
+                "--to",
+                "2",
+            ])
+            .arg("--rules")
+            .arg(&rules_file)
+            .args(extra_args);
+        command
+    };
+
+    // Rename targets are checked before anything is read, even in a dry run.
+    migrate(
+        json!({ "rules": [{ "rename": { "from": ENTRY_AUTOTILE, "to": "../escaped" } }] }),
+        &["--dry-run"],
+    )
+    .assert()
+    .failure()
+    .stderr(format!(
+        "Error: The rename rule for '{}' has an invalid target: Invalid entry name '../escaped': only letters, digits, '_' and '-' are allowed\n",
+        ENTRY_AUTOTILE
+    ));
+    assert!(!v2.exists());
+
+    // An entry that cannot be migrated is reported and the rest still go through.
+    migrate(
+        json!({ "rules": [{ "wrap": { "entry": "binary", "template": "Some({})" } }] }),
+        &[],
+    )
+    .assert()
+    .success()
+    .stdout(format!(
+        "Copying {0}/v1/autotile to v2/autotile\n\
+         Migrated 1 entries from v1 to v2.\n\
+         Skipped 1 entries: 1 invalid.\n",
+        COSMIC_COMP
+    ))
+    .stderr(format!(
+        "Error migrating {}/v1/binary: Cannot wrap 'binary', its value is not valid UTF-8\n",
+        COSMIC_COMP
+    ));
+    assert_eq!(
+        fs::read_to_string(v2.join(ENTRY_AUTOTILE)).unwrap(),
+        VALUE_TRUE
+    );
+    assert!(!v2.join("binary").exists());
+}
+
+#[test]
+fn test_reset_aborts_when_entries_appear_after_prompt() {
+    use std::io::{Read, Write};
+    use std::process::Stdio;
+
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().join("config");
+    let state_home = temp_dir.path().join("state");
+    let write = |entry: &str| {
+        Command::cargo_bin("cosmic-ctl")
+            .unwrap()
+            .env("XDG_CONFIG_HOME", &config_home)
+            .env("XDG_STATE_HOME", &state_home)
+            .env("XDG_RUNTIME_DIR", temp_dir.path())
+            .args([
+                WRITE_OPERATION,
+                "--component",
+                COSMIC_COMP,
+                "--entry",
+                entry,
+                VALUE_TRUE,
+            ])
+            .assert()
+            .success();
+    };
+    write(ENTRY_AUTOTILE);
+
+    let mut reset = std::process::Command::new(assert_cmd::cargo::cargo_bin("cosmic-ctl"))
+        .env("XDG_CONFIG_HOME", &config_home)
+        .env("XDG_STATE_HOME", &state_home)
+        .env("XDG_RUNTIME_DIR", temp_dir.path())
+        .args(["reset", "--xdg-dirs", XDG_CONFIG_DIR])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut prompt = Vec::new();
+    let stdout = reset.stdout.as_mut().unwrap();
+    while !String::from_utf8_lossy(&prompt).contains("[y/N]") {
+        let mut chunk = [0; 256];
+        let read = stdout.read(&mut chunk).unwrap();
+        assert!(read > 0, "reset exited before prompting");
+        prompt.extend_from_slice(&chunk[..read]);
+    }
+    assert!(String::from_utf8_lossy(&prompt).contains("delete 1 configuration entries"));
+
+    write(ENTRY_AUTOTILE_BEHAVIOR);
+
+    reset.stdin.as_mut().unwrap().write_all(b"y\n").unwrap();
+    let output = reset.wait_with_output().unwrap();
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "Error: 1 configuration entries were added while waiting for confirmation, nothing was deleted. Run reset again to review them.\n"
+    );
+
+    let v1 = config_home.join("cosmic").join(COSMIC_COMP).join("v1");
+    assert!(v1.join(ENTRY_AUTOTILE).exists());
+    assert!(v1.join(ENTRY_AUTOTILE_BEHAVIOR).exists());
+}
@@ -0,0 +1,75 @@
+use crate::formats::FileFormat;
+
+/// Reads back a Nix string literal the way Nix does, including the removal of the
+/// indentation shared by the lines of an indented string.
+fn parse_nix_string(literal: &str) -> String {
+    if let Some(quoted) = literal
+        .strip_prefix('"')
+        .and_then(|literal| literal.strip_suffix('"'))
+    {
+        let mut value = String::new();
+        let mut chars = quoted.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next().unwrap() {
+                    'n' => value.push('\n'),
+                    'r' => value.push('\r'),
+                    't' => value.push('\t'),
+                    escaped => value.push(escaped),
+                },
+                '$' if chars.clone().next() == Some('{') => {
+                    panic!("unescaped interpolation in {}", literal)
+                }
+                c => value.push(c),
+            }
+        }
+        return value;
+    }
+
+    let indented = literal
+        .strip_prefix("''\n")
+        .and_then(|literal| literal.strip_suffix("''"))
+        .unwrap_or_else(|| panic!("not a Nix string: {}", literal));
+    let lines: Vec<&str> = indented.split('\n').collect();
+    let indentation = lines
+        .iter()
+        .filter(|line| !line.trim_start_matches(' ').is_empty())
+        .map(|line| line.len() - line.trim_start_matches(' ').len())
+        .min()
+        .unwrap_or(0);
+    let stripped = lines
+        .iter()
+        .map(|line| line.get(indentation..).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    assert!(
+        !stripped.replace("''${", "").contains("${"),
+        "unescaped interpolation in {}",
+        literal
+    );
+    stripped.replace("''${", "${")
+}
+
+#[test]
+fn test_nix_strings_round_trip() {
+    let values = [
+        "true",
+        "",
+        "\"${HOME}/bin\"",
+        "(\n    rules: \"${x}\",\n)",
+        "  a\n  b",
+        "  a\n    b",
+        "a\n\n b",
+        "(\n    name: 'c',\n)",
+        "line\r\nbreak",
+        "\ttab\n\tindented",
+        "back\\slash\n$ {not}",
+        "trailing\n",
+    ];
+
+    for value in values {
+        let literal = FileFormat::Nix.serialize(&value).unwrap();
+        assert_eq!(parse_nix_string(&literal), value, "literal: {}", literal);
+    }
+}
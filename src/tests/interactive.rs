@@ -0,0 +1,47 @@
+use crate::interactive::{parse_version, NameCompleter};
+use inquire::autocompletion::Autocomplete;
+
+fn completer(names: &[&str]) -> NameCompleter {
+    let names: Vec<String> = names.iter().map(|name| name.to_string()).collect();
+    NameCompleter::new(&names)
+}
+
+#[test]
+fn test_name_completer_suggestions() {
+    let mut completer = completer(&["com.system76.CosmicComp", "com.system76.CosmicPanel"]);
+
+    assert_eq!(
+        completer.get_suggestions("panel").unwrap(),
+        vec!["com.system76.CosmicPanel"]
+    );
+    assert_eq!(completer.get_suggestions("").unwrap().len(), 2);
+    assert!(completer.get_suggestions("term").unwrap().is_empty());
+}
+
+#[test]
+fn test_name_completer_completion() {
+    let mut completer = completer(&["autotile", "autotile_behavior", "workspaces"]);
+
+    assert_eq!(
+        completer.get_completion("work", None).unwrap(),
+        Some("workspaces".to_string())
+    );
+    // Ambiguous input is left alone unless a suggestion is highlighted.
+    assert_eq!(completer.get_completion("auto", None).unwrap(), None);
+    assert_eq!(
+        completer
+            .get_completion("auto", Some("autotile_behavior".to_string()))
+            .unwrap(),
+        Some("autotile_behavior".to_string())
+    );
+}
+
+#[test]
+fn test_parse_version_accepts_new_versions() {
+    assert_eq!(parse_version("v1").unwrap(), 1);
+    assert_eq!(parse_version("3").unwrap(), 3);
+    assert_eq!(parse_version(" v12 ").unwrap(), 12);
+    assert!(parse_version("").is_err());
+    assert!(parse_version("vv1").is_err());
+    assert!(parse_version("latest").is_err());
+}
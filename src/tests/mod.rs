@@ -1 +1,5 @@
+mod catalog;
 mod cli;
+mod formats;
+mod interactive;
+mod tui;
@@ -0,0 +1,257 @@
+use crate::tui::{
+    app::{App, ComponentTree, ConfigStore, EntryValue, Mode},
+    ui,
+};
+use ratatui::{
+    backend::TestBackend,
+    crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+    Terminal,
+};
+use std::{collections::BTreeMap, io::Error};
+
+const COSMIC_COMP: &str = "com.system76.CosmicComp";
+const COSMIC_PANEL: &str = "com.system76.CosmicPanel";
+
+/// Store holding entries in memory, keyed by XDG directory.
+#[derive(Default)]
+struct MemoryStore {
+    dirs: BTreeMap<String, ComponentTree>,
+}
+
+impl MemoryStore {
+    fn with_entry(mut self, xdg_dir: &str, component: &str, entry: &str, value: &str) -> Self {
+        self.dirs
+            .entry(xdg_dir.to_string())
+            .or_default()
+            .entry(component.to_string())
+            .or_default()
+            .entry(1)
+            .or_default()
+            .insert(
+                entry.to_string(),
+                EntryValue {
+                    value: Some(value.to_string()),
+                    default: None,
+                },
+            );
+        self
+    }
+
+    fn value(&self, xdg_dir: &str, component: &str, entry: &str) -> Option<String> {
+        self.dirs
+            .get(xdg_dir)?
+            .get(component)?
+            .get(&1)?
+            .get(entry)?
+            .value
+            .clone()
+    }
+}
+
+impl ConfigStore for MemoryStore {
+    fn load(&self, xdg_dir: &str) -> Result<ComponentTree, Error> {
+        Ok(self.dirs.get(xdg_dir).cloned().unwrap_or_default())
+    }
+
+    fn write(
+        &mut self,
+        component: &str,
+        version: u64,
+        entry: &str,
+        value: &str,
+        xdg_dir: &str,
+    ) -> Result<(), Error> {
+        self.dirs
+            .entry(xdg_dir.to_string())
+            .or_default()
+            .entry(component.to_string())
+            .or_default()
+            .entry(version)
+            .or_default()
+            .entry(entry.to_string())
+            .or_default()
+            .value = Some(value.to_string());
+        Ok(())
+    }
+
+    fn delete(
+        &mut self,
+        component: &str,
+        version: u64,
+        entry: &str,
+        xdg_dir: &str,
+    ) -> Result<(), Error> {
+        if let Some(entries) = self
+            .dirs
+            .get_mut(xdg_dir)
+            .and_then(|tree| tree.get_mut(component))
+            .and_then(|versions| versions.get_mut(&version))
+        {
+            entries.remove(entry);
+        }
+        Ok(())
+    }
+}
+
+fn create_app() -> App<MemoryStore> {
+    let store = MemoryStore::default()
+        .with_entry("config", COSMIC_COMP, "autotile", "true")
+        .with_entry("config", COSMIC_COMP, "active_hint", "false")
+        .with_entry("config", COSMIC_PANEL, "entries", "[\"panel\"]")
+        .with_entry("state", COSMIC_COMP, "workspaces", "2");
+
+    App::new(store, vec!["config".to_string(), "state".to_string()], 0)
+}
+
+fn press(app: &mut App<MemoryStore>, code: KeyCode) {
+    app.handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+}
+
+fn type_text(app: &mut App<MemoryStore>, text: &str) {
+    for character in text.chars() {
+        press(app, KeyCode::Char(character));
+    }
+}
+
+fn render(app: &App<MemoryStore>) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+    terminal.draw(|frame| ui::render(frame, app)).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    (0..buffer.area.height)
+        .map(|y| {
+            (0..buffer.area.width)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn test_tui_renders_tree_and_entries() {
+    let app = create_app();
+    let screen = render(&app);
+
+    assert!(screen.contains("config"));
+    assert!(screen.contains("state"));
+    assert!(screen.contains(COSMIC_COMP));
+    assert!(screen.contains(COSMIC_PANEL));
+    assert!(screen.contains("autotile = true"));
+    assert!(screen.contains("active_hint = false"));
+}
+
+#[test]
+fn test_tui_switches_xdg_tabs() {
+    let mut app = create_app();
+
+    press(&mut app, KeyCode::Tab);
+    let screen = render(&app);
+
+    assert_eq!(app.xdg_dir(), "state");
+    assert!(screen.contains("workspaces = 2"));
+    assert!(!screen.contains(COSMIC_PANEL));
+}
+
+#[test]
+fn test_tui_search_filters_components() {
+    let mut app = create_app();
+
+    press(&mut app, KeyCode::Char('/'));
+    type_text(&mut app, "panel");
+    press(&mut app, KeyCode::Enter);
+    let screen = render(&app);
+
+    assert!(screen.contains(COSMIC_PANEL));
+    assert!(!screen.contains(COSMIC_COMP));
+    assert!(screen.contains("Filter: panel"));
+}
+
+#[test]
+fn test_tui_edit_entry() {
+    let mut app = create_app();
+
+    press(&mut app, KeyCode::Right);
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Enter);
+    for _ in 0.."true".len() {
+        press(&mut app, KeyCode::Delete);
+    }
+    type_text(&mut app, "false");
+    app.handle_key(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL));
+
+    assert!(matches!(app.mode, Mode::Normal));
+    assert_eq!(
+        app.store
+            .value("config", COSMIC_COMP, "autotile")
+            .as_deref(),
+        Some("false")
+    );
+    assert!(render(&app).contains("autotile = false"));
+}
+
+#[test]
+fn test_tui_edit_rejects_invalid_ron() {
+    let mut app = create_app();
+
+    press(&mut app, KeyCode::Right);
+    press(&mut app, KeyCode::Enter);
+    type_text(&mut app, "(");
+    app.handle_key(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL));
+
+    assert!(matches!(app.mode, Mode::Edit(_)));
+    assert!(render(&app).contains("Invalid RON"));
+    assert_eq!(
+        app.store
+            .value("config", COSMIC_COMP, "active_hint")
+            .as_deref(),
+        Some("false")
+    );
+}
+
+#[test]
+fn test_tui_delete_and_restore() {
+    let mut app = create_app();
+
+    press(&mut app, KeyCode::Right);
+    press(&mut app, KeyCode::Char('d'));
+    press(&mut app, KeyCode::Char('y'));
+
+    assert_eq!(app.store.value("config", COSMIC_COMP, "active_hint"), None);
+    assert!(!render(&app).contains("active_hint = false"));
+
+    press(&mut app, KeyCode::Char('u'));
+
+    assert_eq!(
+        app.store
+            .value("config", COSMIC_COMP, "active_hint")
+            .as_deref(),
+        Some("false")
+    );
+    assert!(render(&app).contains("active_hint = false"));
+}
+
+#[test]
+fn test_tui_ignores_shortcuts_while_typing() {
+    let mut app = create_app();
+
+    press(&mut app, KeyCode::Right);
+    press(&mut app, KeyCode::Enter);
+    for modifiers in [KeyModifiers::CONTROL, KeyModifiers::ALT] {
+        app.handle_key(KeyEvent::new(KeyCode::Char('x'), modifiers));
+    }
+    type_text(&mut app, "X");
+
+    let Mode::Edit(editor) = &app.mode else {
+        panic!("the editor was closed");
+    };
+    assert_eq!(editor.value(), "Xfalse");
+
+    press(&mut app, KeyCode::Esc);
+    press(&mut app, KeyCode::Char('/'));
+    app.handle_key(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL));
+    type_text(&mut app, "panel");
+    press(&mut app, KeyCode::Enter);
+
+    assert!(render(&app).contains("Filter: panel"));
+}
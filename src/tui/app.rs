@@ -0,0 +1,602 @@
+use crate::config::{
+    delete_configuration, get_cosmic_configurations, get_system_default_dirs,
+    list_configuration_entries, read_configuration, write_raw_configuration,
+};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    io::Error,
+};
+
+/// The value of an entry in the user's configuration and in the system defaults.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EntryValue {
+    pub value: Option<String>,
+    pub default: Option<String>,
+}
+
+/// Entries by component, then version, then entry name.
+pub type ComponentTree = BTreeMap<String, BTreeMap<u64, BTreeMap<String, EntryValue>>>;
+
+/// Where the browser loads and saves configuration entries.
+pub trait ConfigStore {
+    fn load(&self, xdg_dir: &str) -> Result<ComponentTree, Error>;
+    fn write(
+        &mut self,
+        component: &str,
+        version: u64,
+        entry: &str,
+        value: &str,
+        xdg_dir: &str,
+    ) -> Result<(), Error>;
+    fn delete(
+        &mut self,
+        component: &str,
+        version: u64,
+        entry: &str,
+        xdg_dir: &str,
+    ) -> Result<(), Error>;
+}
+
+/// Store backed by the same configuration functions the commands use.
+pub struct FsStore;
+
+impl ConfigStore for FsStore {
+    fn load(&self, xdg_dir: &str) -> Result<ComponentTree, Error> {
+        let mut tree = ComponentTree::new();
+
+        let user_entries = list_configuration_entries(&get_cosmic_configurations(xdg_dir)?);
+        for (component, versions) in user_entries {
+            for (version, entries) in versions {
+                for entry in entries {
+                    let value = read_configuration(&component, &version, &entry, xdg_dir).ok();
+                    tree.entry(component.clone())
+                        .or_default()
+                        .entry(version)
+                        .or_default()
+                        .entry(entry)
+                        .or_default()
+                        .value = value;
+                }
+            }
+        }
+
+        // System defaults only exist for the config directory.
+        if xdg_dir == "config" {
+            for default_dir in get_system_default_dirs().iter().rev() {
+                for (component, versions) in list_configuration_entries(default_dir) {
+                    for (version, entries) in versions {
+                        for entry in entries {
+                            let path = default_dir
+                                .join(&component)
+                                .join(format!("v{}", version))
+                                .join(&entry);
+                            tree.entry(component.clone())
+                                .or_default()
+                                .entry(version)
+                                .or_default()
+                                .entry(entry)
+                                .or_default()
+                                .default = fs::read_to_string(path).ok();
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(tree)
+    }
+
+    fn write(
+        &mut self,
+        component: &str,
+        version: u64,
+        entry: &str,
+        value: &str,
+        xdg_dir: &str,
+    ) -> Result<(), Error> {
+        write_raw_configuration(component, &version, entry, value.as_bytes(), xdg_dir, false)
+            .map(|_| ())
+    }
+
+    fn delete(
+        &mut self,
+        component: &str,
+        version: u64,
+        entry: &str,
+        xdg_dir: &str,
+    ) -> Result<(), Error> {
+        delete_configuration(component, &version, entry, xdg_dir)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Focus {
+    Tree,
+    Entries,
+}
+
+/// A row of the component tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TreeRow {
+    Component { name: String, expanded: bool },
+    Version { component: String, version: u64 },
+}
+
+/// Whether a key types its character, rather than being a shortcut like `Ctrl+S`.
+fn is_typed(key: &KeyEvent) -> bool {
+    !key.modifiers
+        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+}
+
+/// Multi-line editor for an entry value.
+pub struct Editor {
+    pub component: String,
+    pub version: u64,
+    pub entry: String,
+    pub lines: Vec<String>,
+    pub row: usize,
+    pub column: usize,
+    pub error: Option<String>,
+}
+
+impl Editor {
+    fn new(component: &str, version: u64, entry: &str, value: &str) -> Self {
+        let mut lines: Vec<String> = value.split('\n').map(String::from).collect();
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+
+        Self {
+            component: component.to_string(),
+            version,
+            entry: entry.to_string(),
+            row: 0,
+            column: 0,
+            lines,
+            error: None,
+        }
+    }
+
+    pub fn value(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    fn byte_index(&self) -> usize {
+        self.lines[self.row]
+            .char_indices()
+            .nth(self.column)
+            .map_or(self.lines[self.row].len(), |(index, _)| index)
+    }
+
+    fn line_length(&self, row: usize) -> usize {
+        self.lines[row].chars().count()
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char(character) if is_typed(&key) => {
+                let index = self.byte_index();
+                self.lines[self.row].insert(index, character);
+                self.column += 1;
+            }
+            KeyCode::Enter => {
+                let index = self.byte_index();
+                let rest = self.lines[self.row].split_off(index);
+                self.lines.insert(self.row + 1, rest);
+                self.row += 1;
+                self.column = 0;
+            }
+            KeyCode::Backspace if self.column > 0 => {
+                self.column -= 1;
+                let index = self.byte_index();
+                self.lines[self.row].remove(index);
+            }
+            KeyCode::Backspace if self.row > 0 => {
+                let line = self.lines.remove(self.row);
+                self.row -= 1;
+                self.column = self.line_length(self.row);
+                self.lines[self.row].push_str(&line);
+            }
+            KeyCode::Delete if self.column < self.line_length(self.row) => {
+                let index = self.byte_index();
+                self.lines[self.row].remove(index);
+            }
+            KeyCode::Delete if self.row + 1 < self.lines.len() => {
+                let line = self.lines.remove(self.row + 1);
+                self.lines[self.row].push_str(&line);
+            }
+            KeyCode::Left if self.column > 0 => self.column -= 1,
+            KeyCode::Right if self.column < self.line_length(self.row) => self.column += 1,
+            KeyCode::Up if self.row > 0 => {
+                self.row -= 1;
+                self.column = self.column.min(self.line_length(self.row));
+            }
+            KeyCode::Down if self.row + 1 < self.lines.len() => {
+                self.row += 1;
+                self.column = self.column.min(self.line_length(self.row));
+            }
+            KeyCode::Home => self.column = 0,
+            KeyCode::End => self.column = self.line_length(self.row),
+            _ => {}
+        }
+    }
+}
+
+pub enum Mode {
+    Normal,
+    Search,
+    Edit(Editor),
+    ConfirmDelete,
+}
+
+/// An entry deleted during this session, kept so it can be restored.
+struct DeletedEntry {
+    xdg_dir: String,
+    component: String,
+    version: u64,
+    entry: String,
+    value: String,
+}
+
+pub struct App<S: ConfigStore> {
+    pub store: S,
+    pub xdg_dirs: Vec<String>,
+    pub tab: usize,
+    pub tree: ComponentTree,
+    pub expanded: BTreeSet<String>,
+    pub tree_selected: usize,
+    pub entry_selected: usize,
+    pub focus: Focus,
+    pub mode: Mode,
+    pub search: String,
+    pub status: Option<String>,
+    pub should_quit: bool,
+    deleted: Vec<DeletedEntry>,
+}
+
+impl<S: ConfigStore> App<S> {
+    pub fn new(store: S, xdg_dirs: Vec<String>, tab: usize) -> Self {
+        let mut app = Self {
+            store,
+            xdg_dirs,
+            tab,
+            tree: ComponentTree::new(),
+            expanded: BTreeSet::new(),
+            tree_selected: 0,
+            entry_selected: 0,
+            focus: Focus::Tree,
+            mode: Mode::Normal,
+            search: String::new(),
+            status: None,
+            should_quit: false,
+            deleted: Vec::new(),
+        };
+        app.reload();
+        app
+    }
+
+    pub fn xdg_dir(&self) -> &str {
+        &self.xdg_dirs[self.tab]
+    }
+
+    pub fn reload(&mut self) {
+        match self.store.load(&self.xdg_dirs[self.tab]) {
+            Ok(tree) => self.tree = tree,
+            Err(e) => {
+                self.tree = ComponentTree::new();
+                self.status = Some(format!("Failed to load {}: {}", self.xdg_dir(), e));
+            }
+        }
+        self.clamp_selection();
+    }
+
+    fn matches_search(&self, text: &str) -> bool {
+        text.to_lowercase().contains(&self.search.to_lowercase())
+    }
+
+    fn entry_matches_search(&self, name: &str, entry: &EntryValue) -> bool {
+        self.matches_search(name)
+            || entry
+                .value
+                .as_deref()
+                .or(entry.default.as_deref())
+                .is_some_and(|value| self.matches_search(value))
+    }
+
+    fn component_matches_search(&self, component: &str) -> bool {
+        self.search.is_empty()
+            || self.matches_search(component)
+            || self.tree[component].values().any(|entries| {
+                entries
+                    .iter()
+                    .any(|(name, entry)| self.entry_matches_search(name, entry))
+            })
+    }
+
+    /// Visible rows of the component tree, honouring the search query.
+    pub fn tree_rows(&self) -> Vec<TreeRow> {
+        let mut rows = Vec::new();
+
+        for (component, versions) in &self.tree {
+            if !self.component_matches_search(component) {
+                continue;
+            }
+
+            let expanded = !self.search.is_empty() || self.expanded.contains(component);
+            rows.push(TreeRow::Component {
+                name: component.clone(),
+                expanded,
+            });
+
+            if expanded {
+                rows.extend(versions.keys().map(|version| TreeRow::Version {
+                    component: component.clone(),
+                    version: *version,
+                }));
+            }
+        }
+
+        rows
+    }
+
+    /// The component and version whose entries are shown.
+    pub fn selected_version(&self) -> Option<(String, u64)> {
+        match self.tree_rows().get(self.tree_selected)? {
+            TreeRow::Component { name, .. } => {
+                let version = *self.tree.get(name)?.keys().next_back()?;
+                Some((name.clone(), version))
+            }
+            TreeRow::Version { component, version } => Some((component.clone(), *version)),
+        }
+    }
+
+    /// Visible entries of the selected component version.
+    pub fn entry_rows(&self) -> Vec<(String, EntryValue)> {
+        let Some((component, version)) = self.selected_version() else {
+            return Vec::new();
+        };
+        let show_all = self.search.is_empty() || self.matches_search(&component);
+
+        self.tree[&component][&version]
+            .iter()
+            .filter(|(name, entry)| show_all || self.entry_matches_search(name, entry))
+            .map(|(name, entry)| (name.clone(), entry.clone()))
+            .collect()
+    }
+
+    fn clamp_selection(&mut self) {
+        let tree_len = self.tree_rows().len();
+        self.tree_selected = self.tree_selected.min(tree_len.saturating_sub(1));
+
+        let entries_len = self.entry_rows().len();
+        self.entry_selected = self.entry_selected.min(entries_len.saturating_sub(1));
+    }
+
+    fn move_selection(&mut self, down: bool) {
+        let len = match self.focus {
+            Focus::Tree => self.tree_rows().len(),
+            Focus::Entries => self.entry_rows().len(),
+        };
+        let selected = match self.focus {
+            Focus::Tree => &mut self.tree_selected,
+            Focus::Entries => &mut self.entry_selected,
+        };
+
+        if down && *selected + 1 < len {
+            *selected += 1;
+        } else if !down && *selected > 0 {
+            *selected -= 1;
+        }
+
+        if self.focus == Focus::Tree {
+            self.entry_selected = 0;
+        }
+    }
+
+    fn switch_tab(&mut self, forward: bool) {
+        let count = self.xdg_dirs.len();
+        self.tab = if forward {
+            (self.tab + 1) % count
+        } else {
+            (self.tab + count - 1) % count
+        };
+        self.tree_selected = 0;
+        self.entry_selected = 0;
+        self.focus = Focus::Tree;
+        self.reload();
+    }
+
+    fn selected_entry(&self) -> Option<(String, u64, String, EntryValue)> {
+        let (component, version) = self.selected_version()?;
+        let (entry, value) = self.entry_rows().into_iter().nth(self.entry_selected)?;
+        Some((component, version, entry, value))
+    }
+
+    fn start_edit(&mut self) {
+        if let Some((component, version, entry, value)) = self.selected_entry() {
+            let current = value.value.or(value.default).unwrap_or_default();
+            self.mode = Mode::Edit(Editor::new(&component, version, &entry, &current));
+        }
+    }
+
+    fn save_edit(&mut self, editor: Editor) {
+        let value = editor.value();
+
+        if let Err(e) = ron::from_str::<ron::Value>(&value) {
+            self.mode = Mode::Edit(Editor {
+                error: Some(format!("Invalid RON: {}", e)),
+                ..editor
+            });
+            return;
+        }
+
+        let xdg_dir = self.xdg_dir().to_string();
+        match self.store.write(
+            &editor.component,
+            editor.version,
+            &editor.entry,
+            &value,
+            &xdg_dir,
+        ) {
+            Ok(()) => {
+                self.status = Some(format!(
+                    "Saved {}/v{}/{}",
+                    editor.component, editor.version, editor.entry
+                ));
+                self.reload();
+            }
+            Err(e) => {
+                self.mode = Mode::Edit(Editor {
+                    error: Some(format!("Failed to save: {}", e)),
+                    ..editor
+                });
+            }
+        }
+    }
+
+    fn delete_selected(&mut self) {
+        let Some((component, version, entry, value)) = self.selected_entry() else {
+            return;
+        };
+        let Some(current) = value.value else {
+            self.status = Some(format!(
+                "{}/v{}/{} only exists in the system defaults",
+                component, version, entry
+            ));
+            return;
+        };
+
+        let xdg_dir = self.xdg_dir().to_string();
+        match self.store.delete(&component, version, &entry, &xdg_dir) {
+            Ok(()) => {
+                self.status = Some(format!(
+                    "Deleted {}/v{}/{} (press 'u' to restore)",
+                    component, version, entry
+                ));
+                self.deleted.push(DeletedEntry {
+                    xdg_dir,
+                    component,
+                    version,
+                    entry,
+                    value: current,
+                });
+                self.reload();
+            }
+            Err(e) => self.status = Some(format!("Failed to delete: {}", e)),
+        }
+    }
+
+    fn restore_deleted(&mut self) {
+        let Some(deleted) = self.deleted.pop() else {
+            self.status = Some("Nothing to restore".to_string());
+            return;
+        };
+
+        match self.store.write(
+            &deleted.component,
+            deleted.version,
+            &deleted.entry,
+            &deleted.value,
+            &deleted.xdg_dir,
+        ) {
+            Ok(()) => {
+                self.status = Some(format!(
+                    "Restored [{}] {}/v{}/{}",
+                    deleted.xdg_dir, deleted.component, deleted.version, deleted.entry
+                ));
+                self.reload();
+            }
+            Err(e) => {
+                self.status = Some(format!("Failed to restore: {}", e));
+                self.deleted.push(deleted);
+            }
+        }
+    }
+
+    fn toggle_selected_component(&mut self) {
+        if let Some(TreeRow::Component { name, .. }) = self.tree_rows().get(self.tree_selected) {
+            if !self.expanded.remove(name) {
+                self.expanded.insert(name.clone());
+            }
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        match std::mem::replace(&mut self.mode, Mode::Normal) {
+            Mode::Edit(mut editor) => match key.code {
+                KeyCode::Esc => self.status = Some("Edit cancelled".to_string()),
+                KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.save_edit(editor)
+                }
+                _ => {
+                    editor.handle_key(key);
+                    self.mode = Mode::Edit(editor);
+                }
+            },
+            Mode::Search => {
+                match key.code {
+                    KeyCode::Enter => {}
+                    KeyCode::Esc => self.search.clear(),
+                    KeyCode::Backspace => {
+                        self.search.pop();
+                        self.mode = Mode::Search;
+                    }
+                    KeyCode::Char(character) if is_typed(&key) => {
+                        self.search.push(character);
+                        self.mode = Mode::Search;
+                    }
+                    _ => self.mode = Mode::Search,
+                }
+                self.tree_selected = 0;
+                self.entry_selected = 0;
+            }
+            Mode::ConfirmDelete => {
+                if let KeyCode::Char('y') | KeyCode::Char('Y') = key.code {
+                    self.delete_selected();
+                } else {
+                    self.status = Some("Delete cancelled".to_string());
+                }
+            }
+            Mode::Normal => self.handle_normal_key(key),
+        }
+
+        self.clamp_selection();
+    }
+
+    fn handle_normal_key(&mut self, key: KeyEvent) {
+        self.status = None;
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.should_quit = true,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.should_quit = true
+            }
+            KeyCode::Tab => self.switch_tab(true),
+            KeyCode::BackTab => self.switch_tab(false),
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(true),
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(false),
+            KeyCode::Right | KeyCode::Char('l') => self.focus = Focus::Entries,
+            KeyCode::Left | KeyCode::Char('h') => self.focus = Focus::Tree,
+            KeyCode::Char('/') => {
+                self.search.clear();
+                self.mode = Mode::Search;
+            }
+            KeyCode::Enter | KeyCode::Char(' ') if self.focus == Focus::Tree => {
+                self.toggle_selected_component()
+            }
+            KeyCode::Enter | KeyCode::Char('e') if self.focus == Focus::Entries => {
+                self.start_edit()
+            }
+            KeyCode::Char('d')
+                if self.focus == Focus::Entries && self.selected_entry().is_some() =>
+            {
+                self.mode = Mode::ConfirmDelete
+            }
+            KeyCode::Char('u') => self.restore_deleted(),
+            KeyCode::Char('r') => self.reload(),
+            _ => {}
+        }
+    }
+}
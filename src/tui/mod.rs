@@ -0,0 +1,40 @@
+pub mod app;
+pub mod ui;
+
+use crate::tui::app::{App, ConfigStore, FsStore};
+use ratatui::{
+    backend::Backend,
+    crossterm::event::{self, Event, KeyEventKind},
+    Terminal,
+};
+use std::io::Error;
+
+/// Runs the configuration browser until the user quits.
+pub fn run_tui(xdg_dirs: Vec<String>, tab: usize) -> Result<(), Error> {
+    let mut terminal = ratatui::try_init()?;
+    let mut app = App::new(FsStore, xdg_dirs, tab);
+
+    let result = run_app(&mut terminal, &mut app);
+    ratatui::try_restore()?;
+
+    result
+}
+
+fn run_app<B: Backend, S: ConfigStore>(
+    terminal: &mut Terminal<B>,
+    app: &mut App<S>,
+) -> Result<(), Error> {
+    while !app.should_quit {
+        terminal
+            .draw(|frame| ui::render(frame, app))
+            .map_err(|e| Error::other(e.to_string()))?;
+
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                app.handle_key(key);
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,166 @@
+use crate::tui::app::{App, ConfigStore, Focus, Mode, TreeRow};
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Tabs},
+    Frame,
+};
+
+const HELP: &str =
+    "Tab: XDG dir  ←/→: focus  ↑/↓: move  Enter: expand/edit  /: search  d: delete  u: restore  q: quit";
+
+pub fn render<S: ConfigStore>(frame: &mut Frame, app: &App<S>) {
+    let [tabs_area, main_area, status_area] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Min(3),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [tree_area, entries_area] =
+        Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+            .areas(main_area);
+
+    render_tabs(frame, app, tabs_area);
+    render_tree(frame, app, tree_area);
+    render_entries(frame, app, entries_area);
+    render_status(frame, app, status_area);
+
+    if let Mode::Edit(_) = app.mode {
+        render_editor(frame, app, main_area);
+    }
+}
+
+fn focus_block(title: String, focused: bool) -> Block<'static> {
+    let block = Block::default().borders(Borders::ALL).title(title);
+    if focused {
+        block.border_style(Style::new().bold())
+    } else {
+        block
+    }
+}
+
+fn render_tabs<S: ConfigStore>(frame: &mut Frame, app: &App<S>, area: Rect) {
+    let tabs = Tabs::new(app.xdg_dirs.iter().map(String::as_str))
+        .block(Block::default().borders(Borders::ALL).title("cosmic-ctl"))
+        .select(app.tab)
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+
+    frame.render_widget(tabs, area);
+}
+
+fn render_tree<S: ConfigStore>(frame: &mut Frame, app: &App<S>, area: Rect) {
+    let items: Vec<ListItem> = app
+        .tree_rows()
+        .into_iter()
+        .map(|row| match row {
+            TreeRow::Component { name, expanded } => {
+                ListItem::new(format!("{} {}", if expanded { "▾" } else { "▸" }, name))
+            }
+            TreeRow::Version { version, .. } => ListItem::new(format!("    v{}", version)),
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(focus_block(
+            "Components".to_string(),
+            app.focus == Focus::Tree,
+        ))
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default().with_selected(Some(app.tree_selected));
+
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn render_entries<S: ConfigStore>(frame: &mut Frame, app: &App<S>, area: Rect) {
+    let title = match app.selected_version() {
+        Some((component, version)) => format!("{}/v{}", component, version),
+        None => "Entries".to_string(),
+    };
+
+    let items: Vec<ListItem> = app
+        .entry_rows()
+        .into_iter()
+        .map(|(name, entry)| {
+            let (value, style, suffix) = match (&entry.value, &entry.default) {
+                (Some(value), Some(default)) if value != default => {
+                    (value, Style::new().bold(), " (customised)")
+                }
+                (Some(value), _) => (value, Style::new(), ""),
+                (None, Some(default)) => (default, Style::new().dim(), " (default)"),
+                (None, None) => unreachable!("entries always have a value or a default"),
+            };
+            let first_line = value.lines().next().unwrap_or_default();
+            let ellipsis = if value.lines().nth(1).is_some() {
+                " …"
+            } else {
+                ""
+            };
+
+            ListItem::new(Line::from(vec![
+                Span::styled(name, style),
+                Span::raw(" = "),
+                Span::styled(format!("{}{}", first_line, ellipsis), style),
+                Span::raw(suffix).italic(),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(focus_block(title, app.focus == Focus::Entries))
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default();
+    if app.focus == Focus::Entries {
+        state.select(Some(app.entry_selected));
+    }
+
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn render_status<S: ConfigStore>(frame: &mut Frame, app: &App<S>, area: Rect) {
+    let text = match &app.mode {
+        Mode::Search => format!("/{}", app.search),
+        Mode::ConfirmDelete => "Delete this entry? [y/N]".to_string(),
+        Mode::Edit(_) => "Ctrl+S: save  Esc: cancel".to_string(),
+        Mode::Normal => match &app.status {
+            Some(status) => status.clone(),
+            None if !app.search.is_empty() => format!("Filter: {}  ({})", app.search, HELP),
+            None => HELP.to_string(),
+        },
+    };
+
+    frame.render_widget(Paragraph::new(text), area);
+}
+
+fn render_editor<S: ConfigStore>(frame: &mut Frame, app: &App<S>, area: Rect) {
+    let Mode::Edit(editor) = &app.mode else {
+        return;
+    };
+
+    let popup = Rect {
+        x: area.x + area.width / 10,
+        y: area.y + 1,
+        width: area.width - area.width / 5,
+        height: area.height.saturating_sub(2),
+    };
+
+    let mut lines = Vec::new();
+    if let Some(error) = &editor.error {
+        lines.push(Line::from(error.clone()).red());
+        lines.push(Line::default());
+    }
+    let offset = lines.len() as u16;
+    lines.extend(editor.lines.iter().map(|line| Line::from(line.clone())));
+
+    let block = Block::default().borders(Borders::ALL).title(format!(
+        "Edit {}/v{}/{}",
+        editor.component, editor.version, editor.entry
+    ));
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(Paragraph::new(lines).block(block), popup);
+    frame.set_cursor_position((
+        popup.x + 1 + editor.column as u16,
+        popup.y + 1 + offset + editor.row as u16,
+    ));
+}
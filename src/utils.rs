@@ -1,3 +1,7 @@
+use bracoxide::explode;
+use glob::Pattern;
+use std::io::{Error, ErrorKind};
+
 pub fn split_string_respect_braces(input_string: Option<String>) -> Vec<String> {
     match input_string {
         None => Vec::new(),
@@ -34,3 +38,107 @@ pub fn split_string_respect_braces(input_string: Option<String>) -> Vec<String>
         }
     }
 }
+
+/// Builds glob patterns from a comma-separated list, expanding braces and matching
+/// every entry of a bare component (`component`) or component version (`component/v1`).
+pub fn build_patterns(input_string: Option<String>) -> Result<Vec<Pattern>, Error> {
+    split_string_respect_braces(input_string)
+        .into_iter()
+        .flat_map(|pattern| explode(&pattern).unwrap_or_else(|_| vec![pattern.clone()]))
+        .map(|pattern| {
+            let pattern = if !pattern.contains('/') {
+                format!("{}/**", pattern)
+            } else if pattern.matches('/').count() == 1 {
+                format!("{}/*", pattern)
+            } else {
+                pattern
+            };
+
+            Pattern::new(&pattern).map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Invalid pattern '{}': {}", pattern, e),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Include and exclude patterns matched against `component/vN/entry` paths.
+pub struct PathFilter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl PathFilter {
+    pub fn new(include: Option<String>, exclude: Option<String>) -> Result<Self, Error> {
+        Ok(Self {
+            include: build_patterns(include)?,
+            exclude: build_patterns(exclude)?,
+        })
+    }
+
+    /// Whether the path is filtered out, either by not matching any include
+    /// pattern or by matching an exclude pattern.
+    pub fn is_excluded(&self, relative_path: &str) -> bool {
+        let included = self.include.is_empty()
+            || self
+                .include
+                .iter()
+                .any(|pattern| pattern.matches(relative_path));
+
+        !included
+            || self
+                .exclude
+                .iter()
+                .any(|pattern| pattern.matches(relative_path))
+    }
+}
+
+/// Counts skipped entries by reason, for the summary printed after a command.
+#[derive(Default)]
+pub struct SkipSummary {
+    reasons: Vec<(&'static str, usize)>,
+}
+
+impl SkipSummary {
+    pub fn add(&mut self, reason: &'static str) {
+        match self.reasons.iter_mut().find(|(name, _)| *name == reason) {
+            Some((_, count)) => *count += 1,
+            None => self.reasons.push((reason, 1)),
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.reasons.iter().map(|(_, count)| count).sum()
+    }
+
+    /// A line like "Skipped 3 entries: 2 unchanged, 1 not found.", or `None` if nothing was skipped.
+    pub fn describe(&self) -> Option<String> {
+        if self.reasons.is_empty() {
+            return None;
+        }
+
+        let reasons: Vec<String> = self
+            .reasons
+            .iter()
+            .map(|(reason, count)| format!("{} {}", count, reason))
+            .collect();
+
+        Some(format!(
+            "Skipped {} entries: {}.",
+            self.total(),
+            reasons.join(", ")
+        ))
+    }
+}
+
+/// The skip reason reported for an entry that failed with this error.
+pub fn skip_reason(error: &Error) -> &'static str {
+    match error.kind() {
+        ErrorKind::NotFound => "not found",
+        ErrorKind::PermissionDenied => "not permitted",
+        ErrorKind::InvalidInput | ErrorKind::InvalidData => "invalid",
+        _ => "failed",
+    }
+}